@@ -1,7 +1,7 @@
 // Copyright 2019-2024 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 use std::{borrow::Cow, num::NonZeroUsize};
 
 use super::{
@@ -10,18 +10,24 @@ use super::{
         verify_messages_chained, PublicKeyOnG1, PublicKeyOnG2, SignatureOnG1, SignatureOnG2,
     },
 };
+use crate::metrics;
 use crate::shim::clock::ChainEpoch;
 use crate::shim::version::NetworkVersion;
-use crate::utils::net::global_http_client;
+use crate::utils::net::{global_http_client, BackoffConfig};
 use anyhow::Context as _;
 use async_trait::async_trait;
+use backoff::ExponentialBackoff;
 use bls_signatures::Serialize as _;
 use itertools::Itertools as _;
 use lru::LruCache;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize as SerdeDeserialize, Serialize as SerdeSerialize};
 use url::Url;
 
+/// Maximum difference between the local time and the production time of the latest round
+/// observed on the beacon chain, before the clock is deemed wrong. See [`ChainInfo::current_round`].
+pub const MAX_CLOCK_SKEW: Duration = Duration::from_secs(5 * 60);
+
 /// Environmental Variable to ignore `Drand`. Lotus parallel is
 /// `LOTUS_IGNORE_DRAND`
 pub const IGNORE_DRAND_VAR: &str = "IGNORE_DRAND";
@@ -45,6 +51,27 @@ impl DrandNetwork {
     }
 }
 
+/// Signature scheme of a `drand` beacon chain, as reported in the `schemeID` of its info. Chained
+/// beacons sign the previous signature along with the round, unchained ones only the round.
+/// See <https://drand.love/docs/cryptography/#randomness>
+#[derive(SerdeDeserialize, SerdeSerialize, PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub enum DrandScheme {
+    /// Chained scheme with public keys on G1, used by `mainnet`. Beacon chains started before
+    /// `schemeID` was introduced use it as well.
+    #[default]
+    #[serde(rename = "pedersen-bls-chained")]
+    PedersenBlsChained,
+    /// Unchained scheme with signatures on G1, used by `quicknet`.
+    #[serde(rename = "bls-unchained-g1-rfc9380")]
+    BlsUnchainedG1Rfc9380,
+}
+
+impl DrandScheme {
+    pub fn is_chained(&self) -> bool {
+        matches!(self, Self::PedersenBlsChained)
+    }
+}
+
 #[derive(Clone)]
 /// Configuration used when initializing a `Drand` beacon.
 pub struct DrandConfig<'a> {
@@ -57,6 +84,70 @@ pub struct DrandConfig<'a> {
     pub network_type: DrandNetwork,
 }
 
+impl DrandConfig<'_> {
+    /// Checks that the configuration is well-formed, i.e., the public key matches the network
+    /// scheme and the chain info is sane.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let info = &self.chain_info;
+        anyhow::ensure!(info.period > 0, "invalid drand period: {}", info.period);
+        anyhow::ensure!(
+            info.genesis_time > 0,
+            "invalid drand genesis time: {}",
+            info.genesis_time
+        );
+        anyhow::ensure!(
+            info.scheme.is_chained() == self.network_type.is_chained(),
+            "drand scheme {:?} does not match the {:?} network",
+            info.scheme,
+            self.network_type
+        );
+        let public_key = info.decoded_public_key()?;
+        if !info.scheme.is_chained() {
+            PublicKeyOnG2::from_bytes(&public_key).context("invalid drand public key")?;
+        } else {
+            PublicKeyOnG1::from_bytes(&public_key).context("invalid drand public key")?;
+        }
+        let hash = info.decoded_hash()?;
+        info.decoded_group_hash()?;
+        anyhow::ensure!(hash.len() == 32, "invalid drand chain hash length");
+        Ok(())
+    }
+
+    /// Checks that the endpoints are reachable and serve the configured beacon chain, e.g., at
+    /// node startup. The `info` of the chain is fetched from each server, within `timeout`, and
+    /// compared to [`DrandConfig::chain_info`].
+    pub async fn health_check(
+        &self,
+        client: &reqwest::Client,
+        timeout: Duration,
+    ) -> anyhow::Result<()> {
+        for server in &self.servers {
+            let url = server.join(&format!("{}/info", self.chain_info.hash))?;
+            let remote: ChainInfo = async {
+                client
+                    .get(url)
+                    .timeout(timeout)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await
+            }
+            .await
+            .with_context(|| {
+                format!("failed to fetch the chain info from drand server {server}")
+            })?;
+            anyhow::ensure!(
+                remote == self.chain_info,
+                "drand server {server} serves chain {} instead of {}",
+                remote.hash,
+                self.chain_info.hash
+            );
+        }
+        Ok(())
+    }
+}
+
 /// Contains the vector of `BeaconPoint`, which are mappings of epoch to the
 /// `Randomness` beacons used.
 pub struct BeaconSchedule(pub Vec<BeaconPoint>);
@@ -79,7 +170,7 @@ impl BeaconSchedule {
     ) -> Result<Vec<BeaconEntry>, anyhow::Error> {
         let (cb_epoch, curr_beacon) = self.beacon_for_epoch(epoch)?;
         // Before quicknet upgrade, we had "chained" beacons, and so required two entries at a fork
-        if curr_beacon.network().is_chained() {
+        if curr_beacon.scheme().is_chained() {
             let (pb_epoch, _) = self.beacon_for_epoch(parent_epoch)?;
             if cb_epoch != pb_epoch {
                 // Fork logic, take entries from the last two rounds of the new beacon.
@@ -109,7 +200,7 @@ impl BeaconSchedule {
         };
 
         // We only ever need one entry after drand quicknet upgrade (FIP-0063)
-        if curr_beacon.network().is_unchained() {
+        if !curr_beacon.scheme().is_chained() {
             let entry = curr_beacon.entry(max_round).await?;
             Ok(vec![entry])
         } else {
@@ -154,6 +245,10 @@ where
     /// Gets the `drand` network
     fn network(&self) -> DrandNetwork;
 
+    /// Gets the signature scheme of the beacon chain, which determines the number of entries
+    /// per block and the signed messages.
+    fn scheme(&self) -> DrandScheme;
+
     /// Verify beacon entries that are sorted by round.
     fn verify_entries(
         &self,
@@ -179,6 +274,10 @@ impl Beacon for Box<dyn Beacon> {
         self.as_ref().network()
     }
 
+    fn scheme(&self) -> DrandScheme {
+        self.as_ref().scheme()
+    }
+
     fn verify_entries(
         &self,
         entries: &[BeaconEntry],
@@ -212,6 +311,180 @@ pub struct ChainInfo<'a> {
     pub hash: Cow<'a, str>,
     #[serde(rename = "groupHash")]
     pub group_hash: Cow<'a, str>,
+    #[serde(rename = "schemeID", default)]
+    pub scheme: DrandScheme,
+}
+
+/// Errors in a `drand` beacon configuration.
+#[derive(Debug, thiserror::Error)]
+pub enum BeaconConfigError {
+    /// A hex-encoded [`ChainInfo`] field could not be decoded.
+    #[error("invalid hex in drand chain info field `{field}`")]
+    InvalidHex {
+        field: &'static str,
+        #[source]
+        source: hex::FromHexError,
+    },
+}
+
+fn decode_hex_field(field: &'static str, value: &str) -> Result<Vec<u8>, BeaconConfigError> {
+    hex::decode(value).map_err(|source| BeaconConfigError::InvalidHex { field, source })
+}
+
+impl ChainInfo<'_> {
+    /// Returns the chain info as the JSON served by the `info` endpoint of `drand`, e.g., for
+    /// comparing the bundled configuration with the one of a `drand` node.
+    pub fn to_group_json(&self) -> String {
+        serde_json::to_string(self).expect("Infallible, the chain info is plain data")
+    }
+
+    /// Returns the decoded public key of the beacon chain.
+    pub fn decoded_public_key(&self) -> Result<Vec<u8>, BeaconConfigError> {
+        decode_hex_field("public_key", &self.public_key)
+    }
+
+    /// Returns the decoded hash of the beacon chain.
+    pub fn decoded_hash(&self) -> Result<Vec<u8>, BeaconConfigError> {
+        decode_hex_field("hash", &self.hash)
+    }
+
+    /// Returns the decoded hash of the beacon chain group.
+    pub fn decoded_group_hash(&self) -> Result<Vec<u8>, BeaconConfigError> {
+        decode_hex_field("groupHash", &self.group_hash)
+    }
+
+    /// Verifies the signature of the given round against the public key of the beacon chain,
+    /// under its [`DrandScheme`], without querying any `drand` server. The previous signature is
+    /// only part of the signed message for chained schemes, it is ignored otherwise.
+    pub fn verify_beacon(
+        &self,
+        round: u64,
+        prev_signature: &[u8],
+        signature: &[u8],
+    ) -> anyhow::Result<bool> {
+        let public_key = self.decoded_public_key()?;
+        Ok(match self.scheme {
+            DrandScheme::PedersenBlsChained => verify_messages_chained(
+                &PublicKeyOnG1::from_bytes(&public_key)?,
+                &[BeaconEntry::message_chained(round, prev_signature).as_ref()],
+                &[SignatureOnG2::from_bytes(signature)?],
+            ),
+            DrandScheme::BlsUnchainedG1Rfc9380 => PublicKeyOnG2::from_bytes(&public_key)?.verify(
+                BeaconEntry::message_unchained(round),
+                &SignatureOnG1::from_bytes(signature)?,
+            ),
+        })
+    }
+
+    /// Returns the round produced at the given UNIX timestamp, in seconds. Round 1 is produced at
+    /// genesis time, earlier timestamps are mapped to it as well.
+    pub fn round_at(&self, timestamp: u64) -> u64 {
+        let genesis_time = self.genesis_time as u64;
+        if timestamp < genesis_time {
+            return 1;
+        }
+        // The number of periods since genesis, plus one because round 1 starts at genesis time.
+        (timestamp - genesis_time) / self.period as u64 + 1
+    }
+
+    /// Returns the UNIX timestamp, in seconds, the given round is produced at.
+    pub fn round_time(&self, round: u64) -> u64 {
+        self.genesis_time as u64 + round.saturating_sub(1) * self.period as u64
+    }
+
+    /// Returns the distinct rounds covering the epochs from `start_epoch` to `end_epoch`, both
+    /// inclusive, in ascending order, e.g., for fetching their beacon entries in bulk.
+    /// `epoch_time_fn` maps an epoch to the UNIX timestamp, in seconds, its round is produced at.
+    pub fn rounds_for_epochs(
+        &self,
+        start_epoch: ChainEpoch,
+        end_epoch: ChainEpoch,
+        epoch_time_fn: impl Fn(ChainEpoch) -> u64,
+    ) -> Vec<u64> {
+        let mut rounds: Vec<_> = (start_epoch..=end_epoch)
+            .map(|epoch| self.round_at(epoch_time_fn(epoch)))
+            .collect();
+        rounds.sort_unstable();
+        rounds.dedup();
+        rounds
+    }
+
+    /// Returns the number of seconds the local time `now` is ahead of the production time of the
+    /// latest round observed on the beacon chain, negative if it is behind.
+    pub fn clock_skew(&self, now: u64, latest_round: u64) -> i64 {
+        now as i64 - self.round_time(latest_round) as i64
+    }
+
+    /// Returns the round produced at `now`, which callers may take from a trusted time source
+    /// rather than the system clock. Since the round math goes haywire with a wrong clock, a
+    /// warning is logged if `now` precedes the genesis of the beacon chain, or if it is more than
+    /// [`MAX_CLOCK_SKEW`] apart from the latest round observed on the beacon chain, if known.
+    pub fn current_round(&self, now: SystemTime, latest_round: Option<u64>) -> u64 {
+        let now = now
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        if now < self.genesis_time as u64 {
+            tracing::warn!(
+                "Time {now} precedes the drand genesis time {}, is the clock wrong?",
+                self.genesis_time
+            );
+        } else if let Some(latest_round) = latest_round {
+            let skew = self.clock_skew(now, latest_round);
+            if skew.unsigned_abs() > MAX_CLOCK_SKEW.as_secs() {
+                tracing::warn!(
+                    "Time {now} is {skew}s apart from drand round {latest_round}, is the clock wrong?"
+                );
+            }
+        }
+        self.round_at(now)
+    }
+
+    /// Converts into a [`ChainInfo`] owning its fields.
+    pub fn into_owned(self) -> ChainInfo<'static> {
+        ChainInfo {
+            public_key: Cow::Owned(self.public_key.into_owned()),
+            period: self.period,
+            genesis_time: self.genesis_time,
+            hash: Cow::Owned(self.hash.into_owned()),
+            group_hash: Cow::Owned(self.group_hash.into_owned()),
+            scheme: self.scheme,
+        }
+    }
+}
+
+/// Cache of the beacon entries fetched from the `drand` servers, keyed by round. Many Filecoin
+/// epochs may map to the same `drand` round, this avoids fetching it again for each of them.
+#[derive(Debug)]
+pub struct BeaconCache {
+    signatures: Mutex<LruCache<u64, Vec<u8>>>,
+}
+
+impl BeaconCache {
+    /// Creates a cache holding up to `cap` entries.
+    pub fn new(cap: NonZeroUsize) -> Self {
+        Self {
+            signatures: Mutex::new(LruCache::new(cap)),
+        }
+    }
+
+    /// Returns the cached entry for the given round, if any.
+    pub fn get(&self, round: u64) -> Option<BeaconEntry> {
+        let signature = self.signatures.lock().get(&round).cloned();
+        let metric = match signature {
+            Some(_) => &metrics::LRU_CACHE_HIT,
+            None => &metrics::LRU_CACHE_MISS,
+        };
+        metric.get_or_create(&metrics::values::BEACON_ENTRY).inc();
+        signature.map(|signature| BeaconEntry::new(round, signature))
+    }
+
+    /// Caches a successfully fetched entry.
+    pub fn put(&self, entry: &BeaconEntry) {
+        self.signatures
+            .lock()
+            .put(entry.round(), entry.signature().to_vec());
+    }
 }
 
 #[derive(SerdeDeserialize, SerdeSerialize, Debug, Clone)]
@@ -232,14 +505,15 @@ pub struct DrandBeacon {
     network: DrandNetwork,
 
     public_key: Vec<u8>,
-    /// Interval between beacons, in seconds.
-    interval: u64,
-    drand_gen_time: u64,
+    /// Round math of the beacon chain.
+    chain_info: ChainInfo<'static>,
     fil_gen_time: u64,
     fil_round_time: u64,
 
     /// Keeps track of verified beacon entries.
     verified_beacons: RwLock<LruCache<u64, BeaconEntry>>,
+    /// Keeps track of fetched beacon entries.
+    fetched_beacons: BeaconCache,
 }
 
 impl DrandBeacon {
@@ -251,15 +525,17 @@ impl DrandBeacon {
             servers: config.servers.clone(),
             hash: config.chain_info.hash.to_string(),
             network: config.network_type,
-            public_key: hex::decode(config.chain_info.public_key.as_ref())
+            public_key: config
+                .chain_info
+                .decoded_public_key()
                 .expect("invalid static encoding of drand hex public key"),
-            interval: config.chain_info.period as u64,
-            drand_gen_time: config.chain_info.genesis_time as u64,
+            chain_info: config.chain_info.clone().into_owned(),
             fil_round_time: interval,
             fil_gen_time: genesis_ts,
             verified_beacons: RwLock::new(LruCache::new(
                 NonZeroUsize::new(CACHE_SIZE).expect("Infallible"),
             )),
+            fetched_beacons: BeaconCache::new(NonZeroUsize::new(CACHE_SIZE).expect("Infallible")),
         }
     }
 }
@@ -270,13 +546,17 @@ impl Beacon for DrandBeacon {
         self.network
     }
 
+    fn scheme(&self) -> DrandScheme {
+        self.chain_info.scheme
+    }
+
     fn verify_entries<'a>(
         &self,
         entries: &'a [BeaconEntry],
         prev: &'a BeaconEntry,
     ) -> Result<bool, anyhow::Error> {
         let mut validated = vec![];
-        let is_valid = if self.network.is_unchained() {
+        let is_valid = if !self.scheme().is_chained() {
             let mut messages = vec![];
             let mut signatures = vec![];
             let pk = PublicKeyOnG2::from_bytes(&self.public_key)?;
@@ -336,7 +616,12 @@ impl Beacon for DrandBeacon {
     }
 
     async fn entry(&self, round: u64) -> anyhow::Result<BeaconEntry> {
-        let cached: Option<BeaconEntry> = self.verified_beacons.read().peek(&round).cloned();
+        let cached: Option<BeaconEntry> = self
+            .verified_beacons
+            .read()
+            .peek(&round)
+            .cloned()
+            .or_else(|| self.fetched_beacons.get(round));
         match cached {
             Some(cached_entry) => Ok(cached_entry),
             None => {
@@ -378,12 +663,13 @@ impl Beacon for DrandBeacon {
                         anyhow::Ok(server.join(&format!("{}/public/{round}", self.hash))?)
                     })
                     .try_collect()?;
-                Ok(
-                    backoff::future::retry(backoff::ExponentialBackoff::default(), || async {
-                        Ok(fetch_entry(urls.iter().cloned()).await?)
-                    })
-                    .await?,
+                let entry = backoff::future::retry(
+                    ExponentialBackoff::from(BackoffConfig::default()),
+                    || async { Ok(fetch_entry(urls.iter().cloned()).await?) },
                 )
+                .await?;
+                self.fetched_beacons.put(&entry);
+                Ok(entry)
             }
         }
     }
@@ -397,18 +683,10 @@ impl Beacon for DrandBeacon {
             ((fil_epoch as u64 * self.fil_round_time) + self.fil_gen_time) - self.fil_round_time;
         if network_version <= NetworkVersion::V15 {
             // Algorithm for nv15 and below
-            (latest_ts - self.drand_gen_time) / self.interval
+            (latest_ts - self.chain_info.genesis_time as u64) / self.chain_info.period as u64
         } else {
             // Algorithm for nv16 and above
-            if latest_ts < self.drand_gen_time {
-                return 1;
-            }
-
-            let from_genesis = latest_ts - self.drand_gen_time;
-            // we take the time from genesis divided by the periods in seconds, that
-            // gives us the number of periods since genesis.  We also add +1 because
-            // round 1 starts at genesis time.
-            from_genesis / self.interval + 1
+            self.chain_info.round_at(latest_ts)
         }
     }
 }
@@ -1,7 +1,7 @@
 // Copyright 2019-2024 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use super::DrandNetwork;
+use super::{DrandNetwork, DrandScheme};
 use crate::beacon::{Beacon, BeaconEntry};
 use crate::shim::version::NetworkVersion;
 use crate::utils::encoding::blake2b_256;
@@ -26,6 +26,10 @@ impl Beacon for MockBeacon {
         DrandNetwork::Mainnet
     }
 
+    fn scheme(&self) -> DrandScheme {
+        DrandScheme::PedersenBlsChained
+    }
+
     fn verify_entries<'a>(
         &self,
         entries: &'a [BeaconEntry],
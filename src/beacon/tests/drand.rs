@@ -2,11 +2,19 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use crate::{
-    beacon::{Beacon, ChainInfo, DrandBeacon, DrandConfig, DrandNetwork},
+    beacon::{
+        Beacon, BeaconCache, BeaconConfigError, BeaconEntry, ChainInfo, DrandBeacon, DrandConfig,
+        DrandNetwork, DrandScheme,
+    },
+    metrics,
     shim::version::NetworkVersion,
 };
 use serde::{Deserialize, Serialize};
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    num::NonZeroUsize,
+    time::{Duration, SystemTime},
+};
 
 fn new_beacon_mainnet() -> DrandBeacon {
     DrandBeacon::new(
@@ -28,6 +36,7 @@ fn new_beacon_mainnet() -> DrandBeacon {
                 genesis_time: 1595431050,
                 hash: Cow::Borrowed("8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce"),
                 group_hash: Cow::Borrowed("176f93498eac9ca337150b46d21dd58673ea4e3581185f869672e59fa4cb390a"),
+                scheme: DrandScheme::PedersenBlsChained,
             },
             network_type: DrandNetwork::Mainnet,
         },
@@ -54,6 +63,7 @@ fn new_beacon_quicknet() -> DrandBeacon {
                 genesis_time: 1692803367,
                 hash: Cow::Borrowed("52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971"),
                 group_hash: Cow::Borrowed("f477d5c89f21a17c863a7f937c6a6d15859414d2be09cd448d4279af331c5d3e"),
+                scheme: DrandScheme::BlsUnchainedG1Rfc9380,
             },
             network_type: DrandNetwork::Quicknet,
         },
@@ -141,3 +151,256 @@ fn test_max_beacon_round_for_epoch_quicknet() {
         ((1598306400 + 3547000 * 30) - 1692803367 - 30) / 3 + 1
     );
 }
+
+#[test]
+fn test_chain_info_round_math() {
+    let chain_info = ChainInfo {
+        period: 30,
+        genesis_time: 1595431050,
+        ..Default::default()
+    };
+    // Round 1 is produced at genesis, and so are all the earlier timestamps.
+    assert_eq!(chain_info.round_at(0), 1);
+    assert_eq!(chain_info.round_at(1595431050), 1);
+    assert_eq!(chain_info.round_at(1595431050 + 29), 1);
+    assert_eq!(chain_info.round_at(1595431050 + 30), 2);
+    assert_eq!(chain_info.round_at(1595431050 + 30 * 999), 1000);
+}
+
+#[test]
+fn test_chain_info_rounds_for_epochs() {
+    let chain_info = ChainInfo {
+        period: 30,
+        genesis_time: 1595431050,
+        ..Default::default()
+    };
+    // Epochs of 10 seconds, three per round.
+    let epoch_time = |epoch| 1595431050 + epoch as u64 * 10;
+
+    assert_eq!(
+        chain_info.rounds_for_epochs(2, 10, epoch_time),
+        vec![1, 2, 3, 4]
+    );
+    assert_eq!(chain_info.rounds_for_epochs(3, 5, epoch_time), vec![2]);
+    assert!(chain_info.rounds_for_epochs(5, 3, epoch_time).is_empty());
+}
+
+#[test]
+fn test_chain_info_current_round() {
+    let chain_info = ChainInfo {
+        period: 30,
+        genesis_time: 1595431050,
+        ..Default::default()
+    };
+    let at = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+
+    assert_eq!(
+        chain_info.current_round(at(1595431050 + 30 * 999), None),
+        1000
+    );
+    assert_eq!(
+        chain_info.current_round(at(1595431050 + 30 * 999), Some(1000)),
+        1000
+    );
+    assert_eq!(chain_info.round_time(1000), 1595431050 + 30 * 999);
+    assert_eq!(chain_info.clock_skew(1595431050 + 30 * 999 + 5, 1000), 5);
+    assert_eq!(chain_info.clock_skew(1595431050 + 30 * 998, 1000), -30);
+
+    // Before genesis, e.g., with a clock reset to the epoch, round 1 is still returned.
+    assert_eq!(chain_info.current_round(SystemTime::UNIX_EPOCH, None), 1);
+    assert_eq!(chain_info.current_round(at(1595431049), Some(1000)), 1);
+    assert_eq!(chain_info.round_time(0), 1595431050);
+}
+
+#[test]
+fn test_beacon_cache_repeated_round_hits() {
+    let hits = || {
+        metrics::LRU_CACHE_HIT
+            .get_or_create(&metrics::values::BEACON_ENTRY)
+            .get()
+    };
+    let cache = BeaconCache::new(NonZeroUsize::new(2).unwrap());
+    let entry = BeaconEntry::new(4_000_000, vec![0xde, 0xad, 0xbe, 0xef]);
+
+    assert_eq!(cache.get(entry.round()), None);
+    cache.put(&entry);
+
+    let before = hits();
+    assert_eq!(cache.get(entry.round()), Some(entry.clone()));
+    assert_eq!(cache.get(entry.round()), Some(entry));
+    assert!(hits() >= before + 2);
+}
+
+#[test]
+fn test_chain_info_invalid_hex() {
+    let valid = ChainInfo {
+        public_key: "868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31".into(),
+        period: 30,
+        genesis_time: 1595431050,
+        hash: "8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce".into(),
+        group_hash: "176f93498eac9ca337150b46d21dd58673ea4e3581185f869672e59fa4cb390a".into(),
+        scheme: DrandScheme::PedersenBlsChained,
+    };
+    valid.decoded_public_key().unwrap();
+    valid.decoded_hash().unwrap();
+    valid.decoded_group_hash().unwrap();
+
+    let invalid_field = |result: Result<Vec<u8>, BeaconConfigError>| match result.unwrap_err() {
+        BeaconConfigError::InvalidHex { field, .. } => field,
+    };
+    let typo = "not hex";
+    let info = ChainInfo {
+        public_key: typo.into(),
+        ..valid.clone()
+    };
+    assert_eq!(invalid_field(info.decoded_public_key()), "public_key");
+    let info = ChainInfo {
+        hash: typo.into(),
+        ..valid.clone()
+    };
+    assert_eq!(invalid_field(info.decoded_hash()), "hash");
+    let info = ChainInfo {
+        group_hash: typo.into(),
+        ..valid
+    };
+    assert_eq!(invalid_field(info.decoded_group_hash()), "groupHash");
+}
+
+fn drand_chain_info(network: DrandNetwork) -> &'static ChainInfo<'static> {
+    &crate::networks::all_drand_configs()
+        .iter()
+        .find(|config| config.network_type == network)
+        .unwrap()
+        .chain_info
+}
+
+#[test]
+fn test_chain_info_verify_beacon_mainnet() {
+    let chain_info = drand_chain_info(DrandNetwork::Mainnet);
+    // https://api.drand.sh/8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce/public/2
+    let signature_2 = hex::decode("aa18facd2d51b616511d542de6f9af8a3b920121401dad1434ed1db4a565f10e04fad8d9b2b4e3e0094364374caafe9b10478bf75650124831509c638b5a36a7a232ec70289f8751a2adb47fc32eb70b57dc81c39d48cbcac9fec46cdfc31663").unwrap();
+    // https://api.drand.sh/8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce/public/3
+    let signature_3 = hex::decode("a7b0877eaea7a0222f4c39a2c03434c34f5fe3ea47c533d24b88e5c3053b84775ccb78e984addcb55173f40428513f280cc6e0fccc3c89bb1625c7c0b477deb6faae43fc6ec036f09233bf38da16586b3042dd01a7e9ed97c8bafa343cc6071e").unwrap();
+
+    assert!(chain_info
+        .verify_beacon(3, &signature_2, &signature_3)
+        .unwrap());
+    // Wrong round, or wrong previous signature.
+    assert!(!chain_info
+        .verify_beacon(4, &signature_2, &signature_3)
+        .unwrap());
+    assert!(!chain_info
+        .verify_beacon(3, &signature_3, &signature_3)
+        .unwrap());
+    // Not a signature on G2.
+    chain_info
+        .verify_beacon(3, &signature_2, &signature_3[..48])
+        .unwrap_err();
+}
+
+#[test]
+fn test_chain_info_verify_beacon_quicknet() {
+    let chain_info = drand_chain_info(DrandNetwork::Quicknet);
+    // https://api.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/public/2
+    let signature_2 = hex::decode("b6b6a585449b66eb12e875b64fcbab3799861a00e4dbf092d99e969a5eac57dd3f798acf61e705fe4f093db926626807").unwrap();
+    // https://api.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/public/3
+    let signature_3 = hex::decode("b3fab6df720b68cc47175f2c777e86d84187caab5770906f515ff1099cb01e4deaa027075d860823e49477b93c72bd64").unwrap();
+
+    // Unchained, the previous signature is not signed.
+    assert!(chain_info.verify_beacon(2, &[], &signature_2).unwrap());
+    assert!(chain_info
+        .verify_beacon(3, &signature_2, &signature_3)
+        .unwrap());
+    assert!(!chain_info.verify_beacon(3, &[], &signature_2).unwrap());
+}
+
+#[test]
+fn test_chain_info_scheme_serde() {
+    // https://api.drand.sh/8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce/info
+    let mainnet: ChainInfo = serde_json::from_str(r#"{"public_key":"868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31","period":30,"genesis_time":1595431050,"hash":"8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce","groupHash":"176f93498eac9ca337150b46d21dd58673ea4e3581185f869672e59fa4cb390a","schemeID":"pedersen-bls-chained","metadata":{"beaconID":"default"}}"#).unwrap();
+    assert_eq!(mainnet.scheme, DrandScheme::PedersenBlsChained);
+    assert!(mainnet.scheme.is_chained());
+
+    // https://api.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/info
+    let quicknet: ChainInfo = serde_json::from_str(r#"{"public_key":"83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a","period":3,"genesis_time":1692803367,"hash":"52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971","groupHash":"f477d5c89f21a17c863a7f937c6a6d15859414d2be09cd448d4279af331c5d3e","schemeID":"bls-unchained-g1-rfc9380","metadata":{"beaconID":"quicknet"}}"#).unwrap();
+    assert_eq!(quicknet.scheme, DrandScheme::BlsUnchainedG1Rfc9380);
+    assert!(!quicknet.scheme.is_chained());
+    assert_eq!(
+        serde_json::to_value(&quicknet).unwrap()["schemeID"],
+        "bls-unchained-g1-rfc9380"
+    );
+
+    // Beacon chains predating `schemeID` are chained.
+    let legacy: ChainInfo = serde_json::from_str(r#"{"public_key":"8cad0c72c606ab27d36ee06de1d5b2db1faf92e447025ca37575ab3a8aac2eaae83192f846fc9e158bc738423753d000","period":30,"genesis_time":1595873820,"hash":"80c8b872c714f4c00fdd3daa465d5514049f457f01f85a4caf68cdcd394ba039","groupHash":"d9406aaed487f7af71851b4399448e311f2328923d454e971536c05398ce2d9b"}"#).unwrap();
+    assert_eq!(legacy.scheme, DrandScheme::PedersenBlsChained);
+}
+
+async fn serve_chain_info(hash: &str, info: ChainInfo<'static>) -> url::Url {
+    let app = axum::Router::new().route(
+        &format!("/{hash}/info"),
+        axum::routing::get(move || async move { axum::Json(info) }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app.into_make_service())
+            .await
+            .unwrap()
+    });
+    format!("http://{addr}/").parse().unwrap()
+}
+
+#[tokio::test]
+async fn test_drand_config_health_check() {
+    let chain_info = drand_chain_info(DrandNetwork::Mainnet);
+    let config = |server| DrandConfig {
+        servers: vec![server],
+        chain_info: chain_info.clone(),
+        network_type: DrandNetwork::Mainnet,
+    };
+    let client = reqwest::Client::new();
+    let timeout = Duration::from_secs(5);
+
+    let matching = serve_chain_info(&chain_info.hash, chain_info.clone()).await;
+    config(matching)
+        .health_check(&client, timeout)
+        .await
+        .unwrap();
+
+    // Another beacon chain served under the expected hash.
+    let mismatched = serve_chain_info(
+        &chain_info.hash,
+        drand_chain_info(DrandNetwork::Quicknet).clone(),
+    )
+    .await;
+    let err = config(mismatched)
+        .health_check(&client, timeout)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("instead of"), "{err}");
+
+    // Nothing listens on the port of a closed listener.
+    let unreachable = {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        format!("http://{}/", listener.local_addr().unwrap())
+            .parse()
+            .unwrap()
+    };
+    config(unreachable)
+        .health_check(&client, timeout)
+        .await
+        .unwrap_err();
+
+    // A server never answering is given up on after the timeout.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let hanging = format!("http://{}/", listener.local_addr().unwrap())
+        .parse()
+        .unwrap();
+    let start = std::time::Instant::now();
+    config(hanging)
+        .health_check(&client, Duration::from_millis(200))
+        .await
+        .unwrap_err();
+    assert!(start.elapsed() < Duration::from_secs(5));
+    drop(listener);
+}
@@ -127,13 +127,13 @@ impl RawBlockHeader {
             .beacon_for_epoch(self.epoch)
             .map_err(|e| Error::Validation(e.to_string()))?;
         tracing::trace!(
-            "beacon network at {}: {:?}, is_chained: {}",
+            "beacon network at {}: {:?}, scheme: {:?}",
             self.epoch,
             curr_beacon.network(),
-            curr_beacon.network().is_chained()
+            curr_beacon.scheme()
         );
         // Before quicknet upgrade, we had "chained" beacons, and so required two entries at a fork
-        if curr_beacon.network().is_chained() {
+        if curr_beacon.scheme().is_chained() {
             let (pb_epoch, _) = b_schedule
                 .beacon_for_epoch(parent_epoch)
                 .map_err(|e| Error::Validation(e.to_string()))?;
@@ -168,7 +168,7 @@ impl RawBlockHeader {
         }
 
         // We skip verifying the genesis entry when randomness is "chained".
-        if curr_beacon.network().is_chained() && prev_entry.round() == 0 {
+        if curr_beacon.scheme().is_chained() && prev_entry.round() == 0 {
             // This basically means that the drand entry of the first non-genesis tipset isn't verified IF we are starting on Drand mainnet (the "chained" drand)
             // Networks that start on drand quicknet, or other unchained randomness sources, will still verify it
             return Ok(());
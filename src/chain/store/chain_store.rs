@@ -1,7 +1,7 @@
 // Copyright 2019-2024 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use crate::blocks::{CachingBlockHeader, Tipset, TipsetKey, TxMeta};
 use crate::fil_cns;
@@ -81,6 +81,10 @@ pub struct ChainStore<DB> {
 
     /// Needed by the Ethereum mapping.
     pub chain_config: Arc<ChainConfig>,
+
+    /// When the heaviest tipset last changed, or when the store was created. Measured on the
+    /// Tokio clock, so that tests can pause and advance it.
+    head_changed_at: Mutex<tokio::time::Instant>,
 }
 
 impl<DB> BitswapStoreRead for ChainStore<DB>
@@ -141,6 +145,7 @@ where
             validated_blocks,
             eth_mappings,
             chain_config,
+            head_changed_at: Mutex::new(tokio::time::Instant::now()),
         };
 
         Ok(cs)
@@ -149,7 +154,11 @@ where
     /// Sets heaviest tipset within `ChainStore` and store its tipset keys in
     /// the settings store under the [`crate::db::setting_keys::HEAD_KEY`] key.
     pub fn set_heaviest_tipset(&self, ts: Arc<Tipset>) -> Result<(), Error> {
+        let changed = self.settings.read_obj::<TipsetKey>(HEAD_KEY)?.as_ref() != Some(ts.key());
         self.settings.write_obj(HEAD_KEY, ts.key())?;
+        if changed {
+            *self.head_changed_at.lock() = tokio::time::Instant::now();
+        }
         if self.publisher.send(HeadChange::Apply(ts)).is_err() {
             debug!("did not publish head change, no active receivers");
         }
@@ -241,6 +250,12 @@ where
             .expect("failed to load heaviest tipset")
     }
 
+    /// Returns the time elapsed since the heaviest tipset last changed, or since the store was
+    /// created if it has not changed yet.
+    pub fn time_since_head_change(&self) -> Duration {
+        self.head_changed_at.lock().elapsed()
+    }
+
     /// Returns a reference to the publisher of head changes.
     pub fn publisher(&self) -> &Publisher<HeadChange> {
         &self.publisher
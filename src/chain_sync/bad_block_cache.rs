@@ -29,9 +29,16 @@ impl BadBlockCache {
         }
     }
 
-    /// Puts a bad block `Cid` in the cache with a given reason.
-    pub fn put(&self, c: Cid, reason: String) -> Option<String> {
-        self.cache.lock().put(c, reason)
+    /// Marks a block bad with a given reason, refusing the genesis block. Marking the genesis
+    /// block bad would wedge the node permanently, as no chain could be synced anymore. This is
+    /// the only way to put a block in the cache.
+    pub fn mark_bad(&self, c: Cid, reason: String, genesis: &Cid) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            c != *genesis,
+            "refusing to mark the genesis block {c} as bad"
+        );
+        self.cache.lock().put(c, reason);
+        Ok(())
     }
 
     /// Returns `Some` with the reason if the block CID is in bad block cache.
@@ -45,4 +52,17 @@ impl BadBlockCache {
     pub fn peek(&self, c: &Cid) -> Option<String> {
         self.cache.lock().peek(c).cloned()
     }
+
+    /// Returns all the bad block `Cid`s in the cache along with their reasons, sorted by `Cid`.
+    /// This does not update the head position of the keys.
+    pub fn entries(&self) -> Vec<(Cid, String)> {
+        let mut entries: Vec<_> = self
+            .cache
+            .lock()
+            .iter()
+            .map(|(cid, reason)| (*cid, reason.clone()))
+            .collect();
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        entries
+    }
 }
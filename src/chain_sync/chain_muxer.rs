@@ -13,6 +13,7 @@ use crate::chain_sync::{
     bad_block_cache::BadBlockCache,
     metrics,
     network_context::SyncNetworkContext,
+    sync_control::SyncControl,
     sync_state::SyncState,
     tipset_syncer::{
         TipsetProcessor, TipsetProcessorError, TipsetRangeSyncer, TipsetRangeSyncerError,
@@ -150,6 +151,9 @@ pub struct ChainMuxer<DB, M> {
     /// cache
     bad_blocks: Arc<BadBlockCache>,
 
+    /// Switch for stopping and resuming the sync workers.
+    sync_control: Arc<SyncControl>,
+
     /// Incoming network events to be handled by synchronizer
     net_handler: flume::Receiver<NetworkEvent>,
 
@@ -192,6 +196,7 @@ where
             network,
             genesis,
             bad_blocks: Arc::new(BadBlockCache::default()),
+            sync_control: Arc::new(SyncControl::default()),
             net_handler: network_rx,
             mpool,
             tipset_sender,
@@ -207,6 +212,12 @@ where
         self.bad_blocks.clone()
     }
 
+    /// Returns a clone of the switch for stopping and resuming the sync workers, to be used
+    /// outside of chain sync.
+    pub fn sync_control_cloned(&self) -> Arc<SyncControl> {
+        self.sync_control.clone()
+    }
+
     /// Returns a cloned `Arc` of the sync worker state.
     pub fn sync_state_cloned(&self) -> WorkerState {
         self.worker_state.clone()
@@ -939,6 +950,8 @@ enum ChainMuxerState {
     Follow(ChainMuxerFuture<(), ChainMuxerError>),
     /// In stateless mode, forest still connects to the P2P swarm but does not sync to HEAD.
     Stateless(ChainMuxerFuture<(), ChainMuxerError>),
+    /// The sync workers are stopped through [`SyncControl::stop_all`], until resumed.
+    Stopped,
 }
 
 impl<DB, M> Future for ChainMuxer<DB, M>
@@ -949,8 +962,29 @@ where
     type Output = ChainMuxerError;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        self.sync_control.register(cx.waker());
         loop {
+            if self.sync_control.is_stopped()
+                && !matches!(
+                    self.state,
+                    ChainMuxerState::Stopped | ChainMuxerState::Stateless(_)
+                )
+            {
+                // Dropping the future of the current state cancels the in-progress sync.
+                info!("Sync workers stopped");
+                self.state = ChainMuxerState::Stopped;
+                self.worker_state
+                    .write()
+                    .set_stage(crate::chain_sync::SyncStage::Idle);
+            }
             match self.state {
+                ChainMuxerState::Stopped => {
+                    if self.sync_control.is_stopped() {
+                        return Poll::Pending;
+                    }
+                    info!("Sync workers resumed");
+                    self.state = ChainMuxerState::Idle;
+                }
                 ChainMuxerState::Idle => {
                     if self.stateless_mode {
                         info!("Running chain muxer in stateless mode...");
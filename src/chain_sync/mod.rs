@@ -6,6 +6,7 @@ mod chain_muxer;
 pub mod consensus;
 mod metrics;
 mod network_context;
+mod sync_control;
 mod sync_state;
 mod tipset_syncer;
 mod validation;
@@ -16,5 +17,6 @@ pub use self::{
     bad_block_cache::BadBlockCache,
     chain_muxer::{ChainMuxer, SyncConfig},
     consensus::collect_errs,
-    sync_state::{SyncStage, SyncState},
+    sync_control::SyncControl,
+    sync_state::{SyncStage, SyncStallReason, SyncState, SYNC_STALL_THRESHOLD},
 };
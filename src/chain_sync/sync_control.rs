@@ -0,0 +1,44 @@
+// Copyright 2019-2024 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    task::Waker,
+};
+
+use futures::task::AtomicWaker;
+
+/// Thread-safe switch for halting all the sync workers, e.g., during emergency maintenance. While
+/// stopped, the in-progress syncs are dropped and no new one is started until resumed.
+#[derive(Debug, Default)]
+pub struct SyncControl {
+    stopped: AtomicBool,
+    waker: AtomicWaker,
+}
+
+impl SyncControl {
+    /// Stops all the sync workers. Returns `false` if they were already stopped.
+    pub fn stop_all(&self) -> bool {
+        let was_stopped = self.stopped.swap(true, Ordering::SeqCst);
+        self.waker.wake();
+        !was_stopped
+    }
+
+    /// Lets the sync workers start again. Returns `false` if they were not stopped.
+    pub fn resume(&self) -> bool {
+        let was_stopped = self.stopped.swap(false, Ordering::SeqCst);
+        self.waker.wake();
+        was_stopped
+    }
+
+    /// Returns whether the sync workers are stopped.
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::SeqCst)
+    }
+
+    /// Registers the waker of the task running the sync workers, to be woken up when they are
+    /// stopped or resumed.
+    pub(in crate::chain_sync) fn register(&self, waker: &Waker) {
+        self.waker.register(waker);
+    }
+}
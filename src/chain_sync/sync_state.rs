@@ -1,16 +1,24 @@
 // Copyright 2019-2024 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
 use crate::blocks::Tipset;
+use crate::lotus_json::LotusJson;
 use crate::shim::clock::ChainEpoch;
 #[cfg(test)]
 use chrono::TimeZone;
 use chrono::{DateTime, Duration, Utc};
+use cid::Cid;
+use num::BigInt;
+use serde::{Deserialize, Serialize};
+
+/// Time without progress after which a sync worker is considered stalled, see
+/// [`SyncState::detect_stall`].
+pub const SYNC_STALL_THRESHOLD: std::time::Duration = std::time::Duration::from_secs(5 * 60);
 
 /// Current state of the `ChainSyncer` using the `ChainExchange` protocol.
-#[derive(PartialEq, Eq, Debug, Clone, Copy, strum::Display, strum::EnumString)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy, strum::Display, strum::EnumString)]
 #[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
 pub enum SyncStage {
     /// Idle state.
@@ -40,6 +48,33 @@ impl Default for SyncStage {
     }
 }
 
+/// Why a sync worker makes no progress, see [`SyncState::detect_stall`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
+#[serde(tag = "Reason")]
+pub enum SyncStallReason {
+    /// The tipsets to sync contain a block known to be bad.
+    BadBlock {
+        #[schemars(with = "LotusJson<Cid>")]
+        #[serde(rename = "Cid", with = "crate::lotus_json")]
+        cid: Cid,
+    },
+    /// The peers the tipsets are requested from disconnected or did not respond.
+    PeerDisconnected,
+    /// Fetching the messages of the tipsets timed out or failed.
+    MessageFetchTimeout,
+}
+
+impl std::fmt::Display for SyncStallReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BadBlock { cid } => write!(f, "waiting for bad block {cid}"),
+            Self::PeerDisconnected => write!(f, "peer disconnected"),
+            Self::MessageFetchTimeout => write!(f, "message fetch timeout"),
+        }
+    }
+}
+
 /// State of the node's syncing process.
 /// This state is different from the general state of the `ChainSync` process.
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -56,6 +91,23 @@ pub struct SyncState {
     #[cfg_attr(test, arbitrary(gen(maybe_epoch0)))]
     end: Option<DateTime<Utc>>,
     message: String,
+
+    /// Time spent in each stage since the syncing process started.
+    #[cfg_attr(test, arbitrary(gen(arbitrary_stage_durations)))]
+    stage_durations: BTreeMap<SyncStage, std::time::Duration>,
+    /// When the current stage was entered. Not serialized.
+    #[cfg_attr(test, arbitrary(gen(|_| None)))]
+    stage_start: Option<DateTime<Utc>>,
+
+    /// Why the worker makes no progress, if it is stalled and the cause is known.
+    stall_reason: Option<SyncStallReason>,
+    /// When the worker last made progress. Not serialized.
+    #[cfg_attr(test, arbitrary(gen(|_| None)))]
+    last_progress: Option<DateTime<Utc>>,
+    /// Latest known cause of the worker not making progress, reported as the stall reason once
+    /// stalled. Not serialized.
+    #[cfg_attr(test, arbitrary(gen(|_| None)))]
+    stall_cause: Option<SyncStallReason>,
 }
 
 #[cfg(test)]
@@ -66,14 +118,26 @@ fn maybe_epoch0(g: &mut quickcheck::Gen) -> Option<DateTime<Utc>> {
     }
 }
 
+#[cfg(test)]
+fn arbitrary_stage_durations(g: &mut quickcheck::Gen) -> BTreeMap<SyncStage, std::time::Duration> {
+    let durations: Vec<(SyncStage, u64)> = quickcheck::Arbitrary::arbitrary(g);
+    durations
+        .into_iter()
+        .map(|(stage, nanos)| (stage, std::time::Duration::from_nanos(nanos)))
+        .collect()
+}
+
 impl SyncState {
     /// Initializes the syncing state with base and target tipsets and sets
     /// start time.
     pub fn init(&mut self, base: Arc<Tipset>, target: Arc<Tipset>) {
+        let now = Utc::now();
         *self = Self {
             target: Some(target),
             base: Some(base),
-            start: Some(Utc::now()),
+            start: Some(now),
+            stage_start: Some(now),
+            last_progress: Some(now),
             ..Default::default()
         }
     }
@@ -93,6 +157,19 @@ impl SyncState {
         &self.base
     }
 
+    /// Returns the chain weight of the target [`Tipset`], which tells which fork is going to win
+    /// among the sync workers.
+    pub fn target_weight(&self) -> Option<&BigInt> {
+        self.target.as_ref().map(|ts| ts.weight())
+    }
+
+    /// Returns the number of epochs between the base and the target [`Tipset`]s, i.e., an upper
+    /// bound of the number of tipsets left to sync, for estimating the remaining work.
+    pub fn remaining_tipsets(&self) -> Option<ChainEpoch> {
+        let (base, target) = (self.base.as_ref()?, self.target.as_ref()?);
+        Some(target.epoch().saturating_sub(base.epoch()).max(0))
+    }
+
     /// Return the current [`ChainEpoch`]
     pub fn epoch(&self) -> ChainEpoch {
         self.epoch
@@ -109,11 +186,30 @@ impl SyncState {
         }
     }
 
+    /// Returns the time spent in each stage since the syncing process started, up to the last
+    /// stage transition.
+    pub fn stage_durations(&self) -> &BTreeMap<SyncStage, std::time::Duration> {
+        &self.stage_durations
+    }
+
+    /// Accounts for the time spent in the current stage, which is left at `now`.
+    fn leave_stage(&mut self, now: DateTime<Utc>) {
+        if let Some(elapsed) = self
+            .stage_start
+            .replace(now)
+            .and_then(|start| (now - start).to_std().ok())
+        {
+            *self.stage_durations.entry(self.stage).or_default() += elapsed;
+        }
+    }
+
     /// Sets the sync stage for the syncing state. If setting to complete, sets
     /// end timer to now.
     pub fn set_stage(&mut self, stage: SyncStage) {
+        let now = Utc::now();
+        self.leave_stage(now);
         if let SyncStage::Complete = stage {
-            self.end = Some(Utc::now());
+            self.end = Some(now);
         }
         self.stage = stage;
     }
@@ -121,65 +217,122 @@ impl SyncState {
     /// Sets epoch of the sync.
     pub fn set_epoch(&mut self, epoch: ChainEpoch) {
         self.epoch = epoch;
+        self.record_progress(Utc::now());
+    }
+
+    /// Records that the worker made progress at the given time, clearing the stall reason.
+    pub fn record_progress(&mut self, at: DateTime<Utc>) {
+        self.last_progress = Some(at);
+        self.stall_cause = None;
+        self.stall_reason = None;
+    }
+
+    /// Records the cause of the worker not making progress, e.g., of its last error, to be
+    /// reported as the stall reason once stalled.
+    pub fn set_stall_cause(&mut self, cause: SyncStallReason) {
+        self.stall_cause = Some(cause);
+    }
+
+    /// Returns why the worker makes no progress, if it is stalled and the cause is known.
+    pub fn stall_reason(&self) -> Option<&SyncStallReason> {
+        self.stall_reason.as_ref()
+    }
+
+    /// Sets the stall reason to the known cause, if any, if the worker is syncing but has made no
+    /// progress for `threshold` as of `now`, and clears it otherwise.
+    pub fn detect_stall(&mut self, now: DateTime<Utc>, threshold: std::time::Duration) {
+        let syncing = !matches!(self.stage, SyncStage::Idle | SyncStage::Complete);
+        let stalled = self
+            .last_progress
+            .and_then(|at| (now - at).to_std().ok())
+            .is_some_and(|elapsed| elapsed >= threshold);
+        self.stall_reason = if syncing && stalled {
+            self.stall_cause.clone()
+        } else {
+            None
+        };
     }
 
     /// Sets error for the sync.
     pub fn error(&mut self, err: String) {
+        let now = Utc::now();
+        self.leave_stage(now);
         self.message = err;
         self.stage = SyncStage::Error;
-        self.end = Some(Utc::now());
+        self.end = Some(now);
     }
 }
 
 mod lotus_json {
-    use super::SyncState;
+    use super::{SyncStallReason, SyncState};
     use crate::{blocks::Tipset, chain_sync::SyncStage, lotus_json::*};
-    use chrono::{DateTime, Utc};
-    use std::sync::Arc;
+    use chrono::{DateTime, TimeZone as _, Utc};
+    use num::BigInt;
+    use std::{collections::BTreeMap, sync::Arc, time::Duration};
 
     use serde::{Deserialize, Serialize};
     #[cfg(test)]
     use serde_json::json;
 
+    /// The `ActiveSync` of Lotus, without the worker id, along with some Forest extensions
+    /// ignored by Lotus clients.
     #[derive(Serialize, Deserialize, schemars::JsonSchema)]
     #[schemars(rename = "SyncState")]
     #[serde(rename_all = "PascalCase")]
     pub struct SyncStateLotusJson {
         #[schemars(with = "LotusJson<Option<Tipset>>")]
-        #[serde(
-            with = "crate::lotus_json",
-            skip_serializing_if = "Option::is_none",
-            default
-        )]
+        #[serde(with = "crate::lotus_json", default)]
         base: Option<Tipset>,
         #[schemars(with = "LotusJson<Option<Tipset>>")]
+        #[serde(with = "crate::lotus_json", default)]
+        target: Option<Tipset>,
+        /// Chain weight of the target tipset. Derived from `target` and ignored when
+        /// deserializing.
+        #[schemars(with = "LotusJson<Option<BigInt>>")]
         #[serde(
             with = "crate::lotus_json",
             skip_serializing_if = "Option::is_none",
             default
         )]
-        target: Option<Tipset>,
+        target_weight: Option<BigInt>,
+        /// Number of epochs between `base` and `target`. Derived from them and ignored when
+        /// deserializing.
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        remaining_tipsets: Option<i64>,
 
         #[schemars(with = "LotusJson<SyncStage>")]
         #[serde(with = "crate::lotus_json")]
         stage: SyncStage,
-        epoch: i64,
+        /// Older Forest nodes send it as `Epoch`.
+        #[serde(alias = "Epoch")]
+        height: i64,
 
-        #[schemars(with = "LotusJson<Option<DateTime<Utc>>>")]
-        #[serde(
-            with = "crate::lotus_json",
-            skip_serializing_if = "Option::is_none",
-            default
-        )]
-        start: Option<DateTime<Utc>>,
-        #[schemars(with = "LotusJson<Option<DateTime<Utc>>>")]
-        #[serde(
-            with = "crate::lotus_json",
-            skip_serializing_if = "Option::is_none",
-            default
-        )]
-        end: Option<DateTime<Utc>>,
+        /// The zero time of Go if not started, see [`go_zero_time`].
+        #[schemars(with = "LotusJson<DateTime<Utc>>")]
+        #[serde(with = "crate::lotus_json", default = "go_zero_time")]
+        start: DateTime<Utc>,
+        /// The zero time of Go if not ended, see [`go_zero_time`].
+        #[schemars(with = "LotusJson<DateTime<Utc>>")]
+        #[serde(with = "crate::lotus_json", default = "go_zero_time")]
+        end: DateTime<Utc>,
         message: String,
+        /// Time spent in each stage, in nanoseconds as Go's `time.Duration`, keyed by stage.
+        #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+        stage_durations: BTreeMap<String, u64>,
+        /// Why the worker makes no progress, if stalled.
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        stall_reason: Option<SyncStallReason>,
+    }
+
+    /// The zero value of Go's `time.Time`, which Lotus sends for the unset times.
+    fn go_zero_time() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(1, 1, 1, 0, 0, 0)
+            .single()
+            .expect("Infallible, the date is valid")
+    }
+
+    fn from_go_time(time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        Some(time).filter(|time| *time != go_zero_time())
     }
 
     impl HasLotusJson for SyncState {
@@ -189,15 +342,21 @@ mod lotus_json {
         fn snapshots() -> Vec<(serde_json::Value, Self)> {
             vec![(
                 json!({
-                    "Epoch": 0,
+                    "Base": null,
+                    "Target": null,
+                    "Stage": 1,
+                    "Height": 0,
+                    "Start": "0001-01-01T00:00:00Z",
+                    "End": "0001-01-01T00:00:00Z",
                     "Message": "",
-                    "Stage": "header sync",
                 }),
                 Self::default(),
             )]
         }
 
         fn into_lotus_json(self) -> Self::LotusJson {
+            let target_weight = self.target_weight().cloned();
+            let remaining_tipsets = self.remaining_tipsets();
             let Self {
                 base,
                 target,
@@ -206,15 +365,32 @@ mod lotus_json {
                 start,
                 end,
                 message,
+                stage_durations,
+                stage_start: _,
+                stall_reason,
+                last_progress: _,
+                stall_cause: _,
             } = self;
             Self::LotusJson {
                 base: base.as_deref().cloned(),
                 target: target.as_deref().cloned(),
+                target_weight,
+                remaining_tipsets,
                 stage,
-                epoch,
-                start,
-                end,
+                height: epoch,
+                start: start.unwrap_or_else(go_zero_time),
+                end: end.unwrap_or_else(go_zero_time),
                 message,
+                stage_durations: stage_durations
+                    .into_iter()
+                    .map(|(stage, duration)| {
+                        (
+                            stage.to_string(),
+                            u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX),
+                        )
+                    })
+                    .collect(),
+                stall_reason,
             }
         }
 
@@ -222,20 +398,35 @@ mod lotus_json {
             let Self::LotusJson {
                 base,
                 target,
+                target_weight: _,
+                remaining_tipsets: _,
                 stage,
-                epoch,
+                height,
                 start,
                 end,
                 message,
+                stage_durations,
+                stall_reason,
             } = lotus_json;
             Self {
                 base: base.map(Arc::new),
                 target: target.map(Arc::new),
                 stage,
-                epoch,
-                start,
-                end,
+                epoch: height,
+                start: from_go_time(start),
+                end: from_go_time(end),
                 message,
+                // Unknown stages are skipped.
+                stage_durations: stage_durations
+                    .into_iter()
+                    .filter_map(|(stage, nanos)| {
+                        Some((stage.parse().ok()?, Duration::from_nanos(nanos)))
+                    })
+                    .collect(),
+                stage_start: None,
+                stall_reason,
+                last_progress: None,
+                stall_cause: None,
             }
         }
     }
@@ -245,6 +436,186 @@ mod lotus_json {
         assert_all_snapshots::<SyncState>()
     }
 
+    #[cfg(test)]
+    fn state_with_target_weight(weight: u64) -> SyncState {
+        use crate::blocks::{CachingBlockHeader, RawBlockHeader};
+
+        let target = Tipset::from(CachingBlockHeader::new(RawBlockHeader {
+            weight: weight.into(),
+            ..Default::default()
+        }));
+        SyncState {
+            target: Some(Arc::new(target)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn target_weight_serialization() {
+        let json = serde_json::to_value(state_with_target_weight(1234).into_lotus_json()).unwrap();
+        assert_eq!(json["TargetWeight"], json!("1234"));
+
+        let json = serde_json::to_value(SyncState::default().into_lotus_json()).unwrap();
+        assert!(json.get("TargetWeight").is_none());
+    }
+
+    #[cfg(test)]
+    fn tipset_at(epoch: i64) -> Arc<Tipset> {
+        use crate::blocks::{CachingBlockHeader, RawBlockHeader};
+
+        Arc::new(Tipset::from(CachingBlockHeader::new(RawBlockHeader {
+            epoch,
+            ..Default::default()
+        })))
+    }
+
+    #[test]
+    fn remaining_tipsets() {
+        let state = SyncState {
+            base: Some(tipset_at(100)),
+            target: Some(tipset_at(150)),
+            ..Default::default()
+        };
+        assert_eq!(state.remaining_tipsets(), Some(50));
+
+        // A target behind the base, e.g., after a reorg, leaves nothing to sync.
+        let state = SyncState {
+            base: Some(tipset_at(150)),
+            target: Some(tipset_at(100)),
+            ..Default::default()
+        };
+        assert_eq!(state.remaining_tipsets(), Some(0));
+
+        let state = SyncState {
+            target: Some(tipset_at(150)),
+            ..Default::default()
+        };
+        assert_eq!(state.remaining_tipsets(), None);
+    }
+
+    #[test]
+    fn remaining_tipsets_serialization() {
+        let state = SyncState {
+            base: Some(tipset_at(100)),
+            target: Some(tipset_at(150)),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(state.into_lotus_json()).unwrap();
+        assert_eq!(json["RemainingTipsets"], json!(50));
+
+        let json = serde_json::to_value(SyncState::default().into_lotus_json()).unwrap();
+        assert!(json.get("RemainingTipsets").is_none());
+    }
+
+    #[test]
+    fn stage_durations_serialization() {
+        let state = SyncState {
+            stage_durations: BTreeMap::from([
+                (SyncStage::Headers, Duration::from_millis(1500)),
+                (SyncStage::Messages, Duration::from_nanos(42)),
+            ]),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(state.clone().into_lotus_json()).unwrap();
+        assert_eq!(
+            json["StageDurations"],
+            json!({ "header sync": 1_500_000_000, "message sync": 42 })
+        );
+        let round_tripped = SyncState::from_lotus_json(serde_json::from_value(json).unwrap());
+        assert_eq!(round_tripped.stage_durations(), state.stage_durations());
+    }
+
+    #[test]
+    fn stage_durations_recorded() {
+        let mut state = SyncState {
+            stage_start: Some(Utc::now() - chrono::Duration::seconds(5)),
+            ..Default::default()
+        };
+        state.set_stage(SyncStage::Messages);
+        state.set_stage(SyncStage::Complete);
+        let durations = state.stage_durations();
+        assert!(durations[&SyncStage::Headers] >= Duration::from_secs(5));
+        assert!(durations.contains_key(&SyncStage::Messages));
+        assert!(!durations.contains_key(&SyncStage::Complete));
+    }
+
+    #[test]
+    fn stall_reason_serialization() {
+        let cid = "bafy2bzacea3wsdh6y3a36tb3skempjoxqpuyompjbmfeyf34fi3uy6uue42v4"
+            .parse()
+            .unwrap();
+        for (reason, json) in [
+            (
+                SyncStallReason::BadBlock { cid },
+                json!({ "Reason": "BadBlock", "Cid": { "/": cid.to_string() } }),
+            ),
+            (
+                SyncStallReason::PeerDisconnected,
+                json!({ "Reason": "PeerDisconnected" }),
+            ),
+            (
+                SyncStallReason::MessageFetchTimeout,
+                json!({ "Reason": "MessageFetchTimeout" }),
+            ),
+        ] {
+            assert_eq!(serde_json::to_value(&reason).unwrap(), json);
+            assert_eq!(
+                serde_json::from_value::<SyncStallReason>(json).unwrap(),
+                reason
+            );
+        }
+        assert_eq!(
+            SyncStallReason::BadBlock { cid }.to_string(),
+            format!("waiting for bad block {cid}")
+        );
+
+        let json = serde_json::to_value(SyncState::default().into_lotus_json()).unwrap();
+        assert!(json.get("StallReason").is_none());
+    }
+
+    #[test]
+    fn stall_detected() {
+        let now = Utc::now();
+        let threshold = Duration::from_secs(60);
+        let mut state = SyncState::default();
+        state.set_stage(SyncStage::Messages);
+        state.record_progress(now - chrono::Duration::seconds(30));
+        state.set_stall_cause(SyncStallReason::MessageFetchTimeout);
+
+        state.detect_stall(now, threshold);
+        assert_eq!(state.stall_reason(), None);
+        state.detect_stall(now + chrono::Duration::seconds(30), threshold);
+        assert_eq!(
+            state.stall_reason(),
+            Some(&SyncStallReason::MessageFetchTimeout)
+        );
+
+        // Idle workers are not stalled.
+        state.set_stage(SyncStage::Idle);
+        state.detect_stall(now + chrono::Duration::seconds(30), threshold);
+        assert_eq!(state.stall_reason(), None);
+
+        // Progress clears the cause.
+        state.set_stage(SyncStage::Messages);
+        state.record_progress(now);
+        state.detect_stall(now + chrono::Duration::seconds(90), threshold);
+        assert_eq!(state.stall_reason(), None);
+    }
+
+    #[test]
+    fn heaviest_target_identifiable() {
+        let workers = [
+            state_with_target_weight(10),
+            state_with_target_weight(30),
+            state_with_target_weight(20),
+        ];
+        let heaviest = workers
+            .iter()
+            .max_by_key(|worker| worker.target_weight())
+            .unwrap();
+        assert_eq!(heaviest.target_weight(), Some(&BigInt::from(30)));
+    }
+
     #[cfg(test)]
     quickcheck::quickcheck! {
         fn quickcheck(val: SyncState) -> () {
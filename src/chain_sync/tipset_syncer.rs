@@ -46,7 +46,7 @@ use tracing::{debug, error, info, trace, warn};
 
 use crate::chain_sync::{
     bad_block_cache::BadBlockCache, consensus::collect_errs, metrics,
-    network_context::SyncNetworkContext, sync_state::SyncStage, validation::TipsetValidator,
+    network_context::SyncNetworkContext, validation::TipsetValidator, SyncStage, SyncStallReason,
 };
 
 const MAX_TIPSETS_TO_REQUEST: u64 = 100;
@@ -749,7 +749,7 @@ async fn sync_tipset_range<DB: Blockstore + Sync + Send + 'static>(
     {
         Ok(parent_tipsets) => parent_tipsets,
         Err(why) => {
-            tracker.write().error(why.to_string());
+            record_error(&tracker, &why);
             return Err(why);
         }
     };
@@ -785,7 +785,7 @@ async fn sync_tipset_range<DB: Blockstore + Sync + Send + 'static>(
     .await
     {
         error!("Sync messages check state failed for tipset range");
-        tracker.write().error(why.to_string());
+        record_error(&tracker, &why);
         return Err(why);
     };
 
@@ -812,6 +812,31 @@ async fn sync_tipset_range<DB: Blockstore + Sync + Send + 'static>(
     Ok(())
 }
 
+/// Records the error in the worker state, along with the cause of the worker not making progress,
+/// if known.
+fn record_error(
+    tracker: &crate::chain_sync::chain_muxer::WorkerState,
+    why: &TipsetRangeSyncerError,
+) {
+    let cause = match why {
+        TipsetRangeSyncerError::TipsetRangeWithBadBlock(cid, _) => {
+            Some(SyncStallReason::BadBlock { cid: *cid })
+        }
+        TipsetRangeSyncerError::NetworkTipsetQueryFailed(_) => {
+            Some(SyncStallReason::PeerDisconnected)
+        }
+        TipsetRangeSyncerError::NetworkMessageQueryFailed(_) => {
+            Some(SyncStallReason::MessageFetchTimeout)
+        }
+        _ => None,
+    };
+    let mut state = tracker.write();
+    if let Some(cause) = cause {
+        state.set_stall_cause(cause);
+    }
+    state.error(why.to_string());
+}
+
 /// Download headers between the proposed head and the current one available
 /// locally. If they turn out to be on different forks, download more headers up
 /// to a certain limit to try to find a common ancestor.
@@ -827,6 +852,7 @@ async fn sync_headers_in_reverse<DB: Blockstore + Sync + Send + 'static>(
 ) -> Result<NonEmpty<Arc<Tipset>>, TipsetRangeSyncerError> {
     let until_epoch = current_head.epoch() + 1;
     let total_size = proposed_head.epoch() - until_epoch + 1;
+    let genesis = chain_store.genesis_block_header().cid();
 
     let mut accepted_blocks: Vec<Cid> = vec![];
     let mut pending_tipsets = nonempty![proposed_head];
@@ -842,6 +868,7 @@ async fn sync_headers_in_reverse<DB: Blockstore + Sync + Send + 'static>(
             bad_block_cache,
             oldest_pending_tipset.parents(),
             &accepted_blocks,
+            genesis,
         )?;
 
         // Attempt to load the parent tipset from local store
@@ -873,7 +900,12 @@ async fn sync_headers_in_reverse<DB: Blockstore + Sync + Send + 'static>(
         }
 
         let callback = |tipset: Arc<Tipset>| {
-            validate_tipset_against_cache(bad_block_cache, tipset.key(), &accepted_blocks)?;
+            validate_tipset_against_cache(
+                bad_block_cache,
+                tipset.key(),
+                &accepted_blocks,
+                genesis,
+            )?;
             accepted_blocks.extend(tipset.cids());
             tracker.write().set_epoch(tipset.epoch());
             pending_tipsets.push(tipset);
@@ -1219,7 +1251,13 @@ async fn validate_tipset<DB: Blockstore + Send + Sync + 'static>(
                         TipsetRangeSyncerError::TimeTravellingBlock(_, _)
                         | TipsetRangeSyncerError::TipsetParentNotFound(_) => (),
                         why => {
-                            bad_block_cache.put(cid, why.to_string());
+                            if let Err(e) = bad_block_cache.mark_bad(
+                                cid,
+                                why.to_string(),
+                                genesis.min_ticket_block().cid(),
+                            ) {
+                                warn!("{e}");
+                            }
                         }
                     }
                 }
@@ -1622,11 +1660,18 @@ fn validate_tipset_against_cache(
     bad_block_cache: &BadBlockCache,
     tipset: &TipsetKey,
     descendant_blocks: &[Cid],
+    genesis: &Cid,
 ) -> Result<(), TipsetRangeSyncerError> {
     for cid in tipset.to_cids() {
         if let Some(reason) = bad_block_cache.get(&cid) {
             for block_cid in descendant_blocks {
-                bad_block_cache.put(*block_cid, format!("chain contained {cid}"));
+                if let Err(e) = bad_block_cache.mark_bad(
+                    *block_cid,
+                    format!("chain contained {cid}: {reason}"),
+                    genesis,
+                ) {
+                    warn!("{e}");
+                }
             }
             return Err(TipsetRangeSyncerError::TipsetRangeWithBadBlock(cid, reason));
         }
@@ -1691,4 +1736,48 @@ mod test {
         assert_eq!(ts, ts3);
         assert_eq!(ts.weight(), &BigInt::from(10));
     }
+
+    #[test]
+    pub fn test_bad_block_reason_propagated() {
+        let bad_block_cache = BadBlockCache::default();
+        let bad = Tipset::from(mock_block(1234561, 10, 1));
+        let descendants = [
+            *mock_block(1234562, 11, 1).cid(),
+            *mock_block(1234563, 12, 1).cid(),
+        ];
+        let genesis = *mock_block(1234560, 0, 1).cid();
+        let why = "Validating block failed: Invalid state root";
+        bad_block_cache
+            .mark_bad(*bad.min_ticket_block().cid(), why.to_string(), &genesis)
+            .unwrap();
+
+        let err =
+            validate_tipset_against_cache(&bad_block_cache, bad.key(), &descendants, &genesis)
+                .unwrap_err();
+        assert!(matches!(
+            err,
+            TipsetRangeSyncerError::TipsetRangeWithBadBlock(cid, ref reason)
+                if cid == *bad.min_ticket_block().cid() && reason == why
+        ));
+        // The descendants are marked bad with the original reason, as queried by `SyncCheckBad`.
+        for cid in &descendants {
+            assert_eq!(
+                bad_block_cache.peek(cid),
+                Some(format!(
+                    "chain contained {}: {why}",
+                    bad.min_ticket_block().cid()
+                ))
+            );
+        }
+
+        // Tipsets without bad blocks pass.
+        let good = Tipset::from(mock_block(1234564, 10, 1));
+        validate_tipset_against_cache(&bad_block_cache, good.key(), &descendants, &genesis)
+            .unwrap();
+
+        // Not even a chain containing a bad block gets the genesis block marked bad.
+        validate_tipset_against_cache(&bad_block_cache, bad.key(), &[genesis], &genesis)
+            .unwrap_err();
+        assert_eq!(bad_block_cache.peek(&genesis), None);
+    }
 }
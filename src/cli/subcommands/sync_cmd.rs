@@ -24,7 +24,11 @@ pub enum SyncCommands {
         watch: bool,
     },
     /// Check sync status
-    Status,
+    Status {
+        /// Print the status as Prometheus gauges instead
+        #[arg(long)]
+        prometheus: bool,
+    },
     /// Check if a given block is marked bad, and for what reason
     CheckBad {
         #[arg(short)]
@@ -47,7 +51,7 @@ impl SyncCommands {
                 let mut stdout = stdout();
 
                 for _ in ticker {
-                    let resp = SyncState::call(&client, ()).await?;
+                    let resp = SyncState::call(&client, (None,)).await?;
                     let active_syncs = resp.active_syncs;
                     let state = active_syncs
                         .iter()
@@ -97,8 +101,12 @@ impl SyncCommands {
                 }
                 Ok(())
             }
-            Self::Status => {
-                let resp = SyncState::call(&client, ()).await?;
+            Self::Status { prometheus } => {
+                let resp = SyncState::call(&client, (None,)).await?;
+                if prometheus {
+                    print!("{}", resp.encode_prometheus()?);
+                    return Ok(());
+                }
                 let state = resp.active_syncs.first();
 
                 let base = state.base();
@@ -127,10 +135,19 @@ impl SyncCommands {
                 println!("Height diff:\t{}", height_diff.abs());
                 println!("Stage:\t{}", state.stage());
                 println!("Height:\t{}", state.epoch());
+                for (stage, duration) in state.stage_durations() {
+                    println!("Time in {stage}:\t{}s", duration.as_secs());
+                }
+                if let Some(reason) = state.stall_reason() {
+                    println!("Stalled:\t{reason}");
+                }
 
                 if let Some(duration) = elapsed_time {
                     println!("Elapsed time:\t{}s", duration.num_seconds());
                 }
+                if let Some(seconds) = resp.seconds_since_head_change {
+                    println!("Since head change:\t{seconds}s");
+                }
                 Ok(())
             }
             Self::CheckBad { cid } => {
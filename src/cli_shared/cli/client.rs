@@ -66,6 +66,9 @@ pub struct Client {
     pub load_actors: bool,
     /// `TTL` to set for Ethereum `Hash` to `Cid` entries or `None` to never reclaim them.
     pub eth_mapping_ttl: Option<u32>,
+    /// Interval, in seconds, of the background verification of the proof parameter files, which
+    /// repairs the ones gone bad, or `None` to disable it.
+    pub param_revalidation_interval: Option<u32>,
 }
 
 impl Default for Client {
@@ -93,6 +96,7 @@ impl Default for Client {
             ),
             load_actors: true,
             eth_mapping_ttl: None,
+            param_revalidation_interval: None,
         }
     }
 }
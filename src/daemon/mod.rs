@@ -37,7 +37,12 @@ use crate::shim::version::NetworkVersion;
 use crate::state_manager::StateManager;
 use crate::utils;
 use crate::utils::{
-    monitoring::MemStatsTracker, proofs_api::ensure_params_downloaded,
+    monitoring::MemStatsTracker,
+    net::global_http_client,
+    proofs_api::{
+        ensure_params_downloaded, params_cache_dir, spawn_param_revalidator, ParamFetchConfig,
+        SectorSizeOpt, DEFAULT_PARAMETERS,
+    },
     version::FOREST_VERSION_STRING,
 };
 use anyhow::{bail, Context as _};
@@ -138,6 +143,9 @@ pub async fn start_interruptable(opts: CliOpts, config: Config) -> anyhow::Resul
     result
 }
 
+/// Timeout of the `drand` health check performed at startup, per endpoint.
+const DRAND_HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
 // Garbage collection interval, currently set at 10 hours.
 const GC_INTERVAL: Duration = Duration::from_secs(60 * 60 * 10);
 
@@ -147,6 +155,11 @@ pub(super) async fn start(
     config: Config,
     shutdown_send: mpsc::Sender<()>,
 ) -> anyhow::Result<()> {
+    for drand_config in crate::networks::all_drand_configs() {
+        drand_config
+            .validate()
+            .with_context(|| format!("invalid {:?} drand config", drand_config.network_type))?;
+    }
     let chain_config = Arc::new(ChainConfig::from_chain(&config.chain));
     if chain_config.is_testnet() {
         CurrentNetwork::set_global(Network::Testnet);
@@ -210,6 +223,20 @@ pub(super) async fn start(
         &db,
     )
     .await?;
+    chain_config
+        .validate_drand_schedule(genesis_header.timestamp)
+        .context("invalid drand schedule")?;
+    if let Some(drand_config) = chain_config.latest_drand_config() {
+        // Checked in the background, so that an unreachable endpoint does not delay the startup.
+        tokio::spawn(async move {
+            if let Err(e) = drand_config
+                .health_check(&global_http_client(), DRAND_HEALTH_CHECK_TIMEOUT)
+                .await
+            {
+                warn!("drand health check failed: {e:#}");
+            }
+        });
+    }
 
     if config.client.enable_metrics_endpoint {
         // Start Prometheus server port
@@ -362,6 +389,7 @@ pub(super) async fn start(
         opts.stateless,
     )?;
     let bad_blocks = chain_muxer.bad_blocks_cloned();
+    let sync_control = chain_muxer.sync_control_cloned();
     let sync_state = chain_muxer.sync_state_cloned();
     services.spawn(async { Err(anyhow::anyhow!("{}", chain_muxer.await)) });
 
@@ -400,6 +428,7 @@ pub(super) async fn start(
                     keystore: keystore_rpc,
                     mpool,
                     bad_blocks,
+                    sync_control,
                     sync_state,
                     eth_event_handler: Arc::new(EthEventHandler::new()),
                     network_send,
@@ -476,7 +505,7 @@ pub(super) async fn start(
 
     if let Some(validate_from) = config.client.snapshot_height {
         // We've been provided a snapshot and asked to validate it
-        ensure_params_downloaded().await?;
+        ensure_params_downloaded(&chain_config.network).await?;
         // Use the specified HEAD, otherwise take the current HEAD.
         let current_height = config
             .client
@@ -499,6 +528,7 @@ pub(super) async fn start(
         return Ok(());
     }
 
+    let param_revalidation_interval = config.client.param_revalidation_interval;
     // Populate task
     if !opts.stateless && !chain_config.is_devnet() {
         let state_manager = Arc::clone(&state_manager);
@@ -511,7 +541,17 @@ pub(super) async fn start(
     }
 
     if !opts.stateless {
-        ensure_params_downloaded().await?;
+        ensure_params_downloaded(&chain_config.network).await?;
+        if let Some(interval) = param_revalidation_interval {
+            spawn_param_revalidator(
+                params_cache_dir()?,
+                DEFAULT_PARAMETERS.to_owned(),
+                SectorSizeOpt::Keys,
+                Duration::from_secs(interval.into()),
+                true,
+                ParamFetchConfig::default(),
+            );
+        }
     }
     services.spawn(p2p_service.run());
 
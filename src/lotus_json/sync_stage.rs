@@ -4,20 +4,67 @@ use super::*;
 
 use crate::chain_sync::SyncStage;
 
+/// Stage as the integer `SyncStateStage` of Lotus. The description of the stage, sent by older
+/// Forest nodes, is accepted too.
 #[derive(Clone, Serialize, Deserialize, JsonSchema)]
 #[schemars(rename = "SyncStage")]
 pub struct SyncStageLotusJson(
-    #[schemars(with = "String")]
-    #[serde(with = "crate::lotus_json::stringify")]
+    #[schemars(with = "u64")]
+    #[serde(with = "lotus_stage")]
     SyncStage,
 );
 
+mod lotus_stage {
+    use super::*;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StageRepr {
+        Lotus(u64),
+        Description(String),
+    }
+
+    pub fn serialize<S: Serializer>(stage: &SyncStage, serializer: S) -> Result<S::Ok, S::Error> {
+        let stage: u64 = match stage {
+            SyncStage::Idle => 0,
+            SyncStage::Headers => 1,
+            SyncStage::PersistHeaders => 2,
+            SyncStage::Messages => 3,
+            SyncStage::Complete => 4,
+            SyncStage::Error => 5,
+        };
+        serializer.serialize_u64(stage)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SyncStage, D::Error> {
+        match StageRepr::deserialize(deserializer)? {
+            StageRepr::Lotus(0) => Ok(SyncStage::Idle),
+            StageRepr::Lotus(1) => Ok(SyncStage::Headers),
+            StageRepr::Lotus(2) => Ok(SyncStage::PersistHeaders),
+            // Lotus fetches the messages in a separate stage.
+            StageRepr::Lotus(3 | 6) => Ok(SyncStage::Messages),
+            StageRepr::Lotus(4) => Ok(SyncStage::Complete),
+            StageRepr::Lotus(5) => Ok(SyncStage::Error),
+            StageRepr::Lotus(other) => Err(serde::de::Error::custom(format!(
+                "unknown sync stage {other}"
+            ))),
+            StageRepr::Description(description) => {
+                description.parse().map_err(serde::de::Error::custom)
+            }
+        }
+    }
+}
+
 impl HasLotusJson for SyncStage {
     type LotusJson = SyncStageLotusJson;
 
     #[cfg(test)]
     fn snapshots() -> Vec<(serde_json::Value, Self)> {
-        vec![(json!("idle worker"), Self::Idle)]
+        vec![
+            (json!(0), Self::Idle),
+            (json!(3), Self::Messages),
+            (json!(5), Self::Error),
+        ]
     }
 
     fn into_lotus_json(self) -> Self::LotusJson {
@@ -28,3 +75,14 @@ impl HasLotusJson for SyncStage {
         sync_stage
     }
 }
+
+#[test]
+fn legacy_description() {
+    use pretty_assertions::assert_eq;
+
+    let SyncStageLotusJson(stage) = serde_json::from_value(json!("message sync")).unwrap();
+    assert_eq!(stage, SyncStage::Messages);
+    let SyncStageLotusJson(stage) = serde_json::from_value(json!(6)).unwrap();
+    assert_eq!(stage, SyncStage::Messages);
+    assert!(serde_json::from_value::<SyncStageLotusJson>(json!(7)).is_err());
+}
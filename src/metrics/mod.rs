@@ -164,6 +164,8 @@ pub mod values {
     pub const TIPSET: KindLabel = KindLabel::new("tipset");
     /// tipset cache in state manager
     pub const STATE_MANAGER_TIPSET: KindLabel = KindLabel::new("sm_tipset");
+    /// Fetched `drand` beacon entries.
+    pub const BEACON_ENTRY: KindLabel = KindLabel::new("beacon_entry");
 }
 
 pub fn default_histogram() -> Histogram {
@@ -1,7 +1,7 @@
 // Copyright 2019-2024 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use crate::beacon::{ChainInfo, DrandConfig, DrandNetwork};
+use crate::beacon::{ChainInfo, DrandConfig, DrandNetwork, DrandScheme};
 use once_cell::sync::Lazy;
 use std::borrow::Cow;
 
@@ -22,6 +22,7 @@ pub(super) static DRAND_MAINNET: Lazy<DrandConfig<'static>> = Lazy::new(|| {
             genesis_time: 1595431050,
             hash: Cow::Borrowed("8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce"),
             group_hash: Cow::Borrowed("176f93498eac9ca337150b46d21dd58673ea4e3581185f869672e59fa4cb390a"),
+            scheme: DrandScheme::PedersenBlsChained,
         },
         network_type: DrandNetwork::Mainnet,
     }
@@ -44,6 +45,7 @@ pub(super) static DRAND_QUICKNET: Lazy<DrandConfig<'static>> = Lazy::new(|| {
             genesis_time: 1692803367,
             hash: Cow::Borrowed("52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971"),
             group_hash: Cow::Borrowed("f477d5c89f21a17c863a7f937c6a6d15859414d2be09cd448d4279af331c5d3e"),
+            scheme: DrandScheme::BlsUnchainedG1Rfc9380,
         },
         network_type: DrandNetwork::Quicknet,
     }
@@ -61,11 +63,19 @@ pub(super) static DRAND_INCENTINET: Lazy<DrandConfig<'static>> = Lazy::new(|| {
             genesis_time: 1595873820,
             hash: Cow::Borrowed("80c8b872c714f4c00fdd3daa465d5514049f457f01f85a4caf68cdcd394ba039"),
             group_hash: Cow::Borrowed("d9406aaed487f7af71851b4399448e311f2328923d454e971536c05398ce2d9b"),
+            scheme: DrandScheme::PedersenBlsChained,
         },
         network_type: DrandNetwork::Incentinet,
     }
 });
 
+/// Returns all the `drand` networks shipped with this build.
+pub fn all_drand_configs() -> &'static [&'static DrandConfig<'static>] {
+    static ALL: Lazy<[&'static DrandConfig<'static>; 3]> =
+        Lazy::new(|| [&*DRAND_MAINNET, &*DRAND_QUICKNET, &*DRAND_INCENTINET]);
+    &*ALL
+}
+
 #[cfg(test)]
 mod tests {
     use url::Url;
@@ -74,6 +84,43 @@ mod tests {
     use crate::utils::{net::global_http_client, retry, RetryArgs};
     use std::time::Duration;
 
+    #[test]
+    fn test_all_drand_configs() {
+        let networks: Vec<_> = all_drand_configs()
+            .iter()
+            .map(|config| config.network_type)
+            .collect();
+        assert_eq!(
+            networks,
+            [
+                DrandNetwork::Mainnet,
+                DrandNetwork::Quicknet,
+                DrandNetwork::Incentinet
+            ]
+        );
+        for config in all_drand_configs() {
+            config.validate().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_group_json_round_trip() {
+        let json = DRAND_MAINNET.chain_info.to_group_json();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        for key in [
+            "public_key",
+            "period",
+            "genesis_time",
+            "hash",
+            "groupHash",
+            "schemeID",
+        ] {
+            assert!(value.get(key).is_some(), "{key} missing from {json}");
+        }
+        let chain_info: ChainInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(chain_info, DRAND_MAINNET.chain_info);
+    }
+
     #[tokio::test]
     async fn test_drand_mainnet() {
         test_drand(&DRAND_MAINNET).await
@@ -4,6 +4,7 @@
 use std::str::FromStr;
 
 use ahash::HashMap;
+use anyhow::Context as _;
 use cid::Cid;
 use fil_actors_shared::v13::runtime::Policy;
 use itertools::Itertools;
@@ -29,6 +30,7 @@ pub use actors_bundle::{
 };
 
 mod drand;
+pub use drand::all_drand_configs;
 
 pub mod butterflynet;
 pub mod calibnet;
@@ -369,16 +371,35 @@ impl ChainConfig {
             .max(self.genesis_network)
     }
 
-    pub fn get_beacon_schedule(&self, genesis_ts: u64) -> BeaconSchedule {
-        let ds_iter = match self.network {
-            NetworkChain::Mainnet => mainnet::DRAND_SCHEDULE.iter(),
-            NetworkChain::Calibnet => calibnet::DRAND_SCHEDULE.iter(),
-            NetworkChain::Butterflynet => butterflynet::DRAND_SCHEDULE.iter(),
-            NetworkChain::Devnet(_) => devnet::DRAND_SCHEDULE.iter(),
-        };
+    fn drand_schedule(&self) -> &'static [DrandPoint<'static>] {
+        match self.network {
+            NetworkChain::Mainnet => mainnet::DRAND_SCHEDULE.as_slice(),
+            NetworkChain::Calibnet => calibnet::DRAND_SCHEDULE.as_slice(),
+            NetworkChain::Butterflynet => butterflynet::DRAND_SCHEDULE.as_slice(),
+            NetworkChain::Devnet(_) => devnet::DRAND_SCHEDULE.as_slice(),
+        }
+    }
 
+    /// Returns the configuration of the latest `drand` beacon of the network, i.e., the one used
+    /// for new blocks.
+    pub fn latest_drand_config(&self) -> Option<&'static DrandConfig<'static>> {
+        self.drand_schedule().last().map(|point| &**point.config)
+    }
+
+    /// Checks that the drand schedule of the network is consistent for a chain with the given
+    /// genesis timestamp, see [`validate_drand_schedule`].
+    pub fn validate_drand_schedule(&self, genesis_ts: u64) -> anyhow::Result<()> {
+        validate_drand_schedule(
+            self.drand_schedule(),
+            genesis_ts,
+            self.block_delay_secs.into(),
+        )
+    }
+
+    pub fn get_beacon_schedule(&self, genesis_ts: u64) -> BeaconSchedule {
         BeaconSchedule(
-            ds_iter
+            self.drand_schedule()
+                .iter()
                 .map(|dc| BeaconPoint {
                     height: dc.height,
                     beacon: Box::new(DrandBeacon::new(
@@ -509,6 +530,52 @@ pub fn calculate_expected_epoch(
     now_timestamp.saturating_sub(genesis_timestamp) / block_delay as u64
 }
 
+/// Checks that the epochs of the drand schedule are strictly increasing, and that each beacon
+/// chain has started by the time it is activated, as no rounds would be available for the
+/// epochs before otherwise. This catches the schedules assembled wrong at startup.
+fn validate_drand_schedule(
+    schedule: &[DrandPoint],
+    genesis_ts: u64,
+    block_delay_secs: u64,
+) -> anyhow::Result<()> {
+    anyhow::ensure!(!schedule.is_empty(), "empty drand schedule");
+    for (prev, next) in schedule.iter().tuple_windows() {
+        anyhow::ensure!(
+            next.height != prev.height,
+            "duplicate drand schedule epoch {}",
+            next.height
+        );
+        anyhow::ensure!(
+            next.height > prev.height,
+            "drand schedule epochs out of order: {} after {}",
+            next.height,
+            prev.height
+        );
+    }
+    for point in schedule {
+        let info = &point.config.chain_info;
+        anyhow::ensure!(
+            info.period > 0,
+            "invalid {:?} drand period: {}",
+            point.config.network_type,
+            info.period
+        );
+        let activation = u64::try_from(point.height)
+            .ok()
+            .and_then(|height| height.checked_mul(block_delay_secs))
+            .and_then(|delay| delay.checked_add(genesis_ts))
+            .with_context(|| format!("invalid drand schedule epoch {}", point.height))?;
+        anyhow::ensure!(
+            u64::try_from(info.genesis_time).is_ok_and(|genesis_time| genesis_time <= activation),
+            "{:?} drand beacon starting at {} is activated before, at epoch {} ({activation})",
+            point.config.network_type,
+            info.genesis_time,
+            point.height
+        );
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -643,4 +710,36 @@ mod tests {
         ChainConfig::devnet();
         ChainConfig::butterflynet();
     }
+
+    #[test]
+    fn test_validate_drand_schedule() {
+        use crate::networks::drand::{DRAND_MAINNET, DRAND_QUICKNET};
+
+        const MAINNET_GENESIS: u64 = 1598306400;
+        const CALIBNET_GENESIS: u64 = 1667326380;
+        for (network, genesis_ts) in [
+            (NetworkChain::Mainnet, MAINNET_GENESIS),
+            (NetworkChain::Calibnet, CALIBNET_GENESIS),
+        ] {
+            let config = ChainConfig::from_chain(&network);
+            config.validate_drand_schedule(genesis_ts).unwrap();
+        }
+
+        let point = |height, config| DrandPoint { height, config };
+        let valid = [point(0, &DRAND_MAINNET), point(4_000_000, &DRAND_QUICKNET)];
+        validate_drand_schedule(&valid, MAINNET_GENESIS, 30).unwrap();
+
+        let out_of_order = [point(100, &DRAND_MAINNET), point(0, &DRAND_QUICKNET)];
+        let err = validate_drand_schedule(&out_of_order, MAINNET_GENESIS, 30).unwrap_err();
+        assert!(err.to_string().contains("out of order"), "{err}");
+
+        let duplicate = [point(0, &DRAND_MAINNET), point(0, &DRAND_QUICKNET)];
+        let err = validate_drand_schedule(&duplicate, MAINNET_GENESIS, 30).unwrap_err();
+        assert!(err.to_string().contains("duplicate"), "{err}");
+
+        // Quicknet only started in 2023.
+        let not_started = [point(0, &DRAND_QUICKNET)];
+        validate_drand_schedule(&not_started, MAINNET_GENESIS, 30).unwrap_err();
+        validate_drand_schedule(&[], MAINNET_GENESIS, 30).unwrap_err();
+    }
 }
@@ -1,10 +1,14 @@
 // Copyright 2019-2024 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use crate::chain::index::ResolveNullTipset;
+use crate::lotus_json::{lotus_json_with_self, LotusJson};
 use crate::rpc::{ApiPaths, Ctx, Permission, RpcMethod, ServerError};
 use crate::{beacon::BeaconEntry, shim::clock::ChainEpoch};
 use anyhow::Result;
 use fvm_ipld_blockstore::Blockstore;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
 /// `BeaconGetEntry` returns the beacon entry for the given Filecoin epoch. If
 /// the entry has not yet been produced, the call will block until the entry
@@ -30,3 +34,210 @@ impl RpcMethod<1> for BeaconGetEntry {
         Ok(e)
     }
 }
+
+/// `BeaconGetEntryInfo` returns the latest beacon entry found on chain at the
+/// given Filecoin epoch, along with the round the `drand` round math expects for
+/// that epoch. Useful for debugging randomness issues.
+pub enum BeaconGetEntryInfo {}
+impl RpcMethod<1> for BeaconGetEntryInfo {
+    const NAME: &'static str = "Forest.BeaconGetEntryInfo";
+    const PARAM_NAMES: [&'static str; 1] = ["epoch"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Read;
+
+    type Params = (ChainEpoch,);
+    type Ok = BeaconEntryInfo;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore>,
+        (epoch,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let tipset = ctx.chain_index().tipset_by_height(
+            epoch,
+            ctx.chain_store().heaviest_tipset(),
+            ResolveNullTipset::TakeOlder,
+        )?;
+        let (round, signature) = ctx.chain_index().latest_beacon_entry(tipset)?.into_parts();
+        let (_, beacon) = ctx.beacon().beacon_for_epoch(epoch)?;
+        let expected_round =
+            beacon.max_beacon_round_for_epoch(ctx.state_manager.get_network_version(epoch), epoch);
+        Ok(BeaconEntryInfo {
+            round,
+            signature,
+            expected_round,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct BeaconEntryInfo {
+    /// Round of the latest beacon entry on chain.
+    pub round: u64,
+    /// Signature of the latest beacon entry on chain.
+    #[schemars(with = "LotusJson<Vec<u8>>")]
+    #[serde(with = "crate::lotus_json")]
+    pub signature: Vec<u8>,
+    /// Round expected for the epoch.
+    pub expected_round: u64,
+}
+lotus_json_with_self!(BeaconEntryInfo);
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::blocks::{CachingBlockHeader, RawBlockHeader, Tipset};
+    use crate::chain::ChainStore;
+    use crate::chain_sync::SyncConfig;
+    use crate::db::MemoryDB;
+    use crate::key_management::{KeyStore, KeyStoreConfig};
+    use crate::message_pool::{MessagePool, MpoolRpcProvider};
+    use crate::networks::ChainConfig;
+    use crate::rpc::eth::filter::EthEventHandler;
+    use crate::rpc::reflect::{jsonrpc_types::RequestParameters, Params, RpcMethodExt as _};
+    use crate::rpc::RPCState;
+    use crate::state_manager::StateManager;
+    use crate::utils::db::CborStoreExt as _;
+    use tokio::{sync::RwLock, task::JoinSet};
+
+    /// Epoch of the head of the test chain, holding the only beacon entry.
+    const HEAD_EPOCH: ChainEpoch = 10;
+    /// Round of the beacon entry of the head.
+    const HEAD_ROUND: u64 = 42;
+
+    /// Returns the RPC state of a devnet node whose chain is made of the genesis and a head at
+    /// [`HEAD_EPOCH`], along with the genesis timestamp.
+    fn ctx() -> (Arc<RPCState<MemoryDB>>, u64) {
+        let (network_send, _) = flume::bounded(5);
+        let (tipset_send, _) = flume::bounded(5);
+        let mut services = JoinSet::new();
+        let db = Arc::new(MemoryDB::default());
+        let chain_config = Arc::new(ChainConfig::devnet());
+        let genesis_time = chain_config
+            .latest_drand_config()
+            .unwrap()
+            .chain_info
+            .genesis_time as u64
+            + 1000;
+
+        let genesis = CachingBlockHeader::new(RawBlockHeader {
+            timestamp: genesis_time,
+            ..Default::default()
+        });
+        let head = CachingBlockHeader::new(RawBlockHeader {
+            parents: Tipset::from(&genesis).key().clone(),
+            epoch: HEAD_EPOCH,
+            timestamp: genesis_time + HEAD_EPOCH as u64 * chain_config.block_delay_secs as u64,
+            beacon_entries: vec![BeaconEntry::new(HEAD_ROUND, vec![0xde, 0xad, 0xbe, 0xef])],
+            ..Default::default()
+        });
+        db.put_cbor_default(&genesis).unwrap();
+        db.put_cbor_default(&head).unwrap();
+
+        let cs = Arc::new(
+            ChainStore::new(db.clone(), db.clone(), db, chain_config.clone(), genesis).unwrap(),
+        );
+        cs.set_heaviest_tipset(Arc::new(Tipset::from(head)))
+            .unwrap();
+        let state_manager = Arc::new(
+            StateManager::new(
+                cs.clone(),
+                chain_config.clone(),
+                Arc::new(SyncConfig::default()),
+            )
+            .unwrap(),
+        );
+        let provider = MpoolRpcProvider::new(cs.publisher().clone(), state_manager.clone());
+        let mpool = MessagePool::new(
+            provider,
+            "test".to_string(),
+            network_send.clone(),
+            Default::default(),
+            chain_config,
+            &mut services,
+        )
+        .unwrap();
+
+        let state = Arc::new(RPCState {
+            state_manager,
+            keystore: Arc::new(RwLock::new(KeyStore::new(KeyStoreConfig::Memory).unwrap())),
+            mpool: Arc::new(mpool),
+            bad_blocks: Default::default(),
+            sync_control: Default::default(),
+            sync_state: Arc::new(parking_lot::RwLock::new(Default::default())),
+            eth_event_handler: Arc::new(EthEventHandler::new()),
+            network_send,
+            network_name: "test".to_owned(),
+            start_time: chrono::Utc::now(),
+            shutdown: tokio::sync::mpsc::channel(1).0, // dummy for tests
+            tipset_send,
+        });
+        (state, genesis_time)
+    }
+
+    #[tokio::test]
+    async fn beacon_get_entry_info() {
+        let (ctx, genesis_time) = ctx();
+
+        // The request parameters round-trip through JSON.
+        let serde_json::Value::Array(params) =
+            BeaconGetEntryInfo::request_params((HEAD_EPOCH,)).unwrap()
+        else {
+            panic!("parameters are passed by position");
+        };
+        let (epoch,) = <(ChainEpoch,) as Params<1>>::parse(
+            Some(RequestParameters::ByPosition(params)),
+            BeaconGetEntryInfo::PARAM_NAMES,
+            openrpc_types::ParamStructure::Either,
+            BeaconGetEntryInfo::N_REQUIRED_PARAMS,
+        )
+        .unwrap();
+        assert_eq!(epoch, HEAD_EPOCH);
+
+        let info = BeaconGetEntryInfo::handle(ctx.clone(), (epoch,))
+            .await
+            .unwrap();
+        assert_eq!(info.round, HEAD_ROUND);
+        assert_eq!(info.signature, [0xde, 0xad, 0xbe, 0xef]);
+        // The round of the latest timestamp of the epoch, see `ChainInfo::round_at`.
+        let chain_info = &ctx.chain_config().latest_drand_config().unwrap().chain_info;
+        let block_delay = ctx.chain_config().block_delay_secs as u64;
+        let latest_ts = genesis_time + (epoch as u64 - 1) * block_delay;
+        assert_eq!(info.expected_round, chain_info.round_at(latest_ts));
+        assert_eq!(
+            info.expected_round,
+            (latest_ts - chain_info.genesis_time as u64) / chain_info.period as u64 + 1
+        );
+
+        // Epochs past the head are unknown.
+        BeaconGetEntryInfo::handle(ctx.clone(), (HEAD_EPOCH + 1,))
+            .await
+            .unwrap_err();
+        // So is the genesis, without beacon entries.
+        BeaconGetEntryInfo::handle(ctx, (0,)).await.unwrap_err();
+    }
+
+    #[test]
+    fn beacon_entry_info_round_trip() {
+        let info = BeaconEntryInfo {
+            round: 4_000_000,
+            signature: vec![0xde, 0xad, 0xbe, 0xef],
+            expected_round: 4_000_001,
+        };
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "Round": 4_000_000,
+                "Signature": "3q2+7w==",
+                "ExpectedRound": 4_000_001,
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<BeaconEntryInfo>(json).unwrap(),
+            info
+        );
+    }
+}
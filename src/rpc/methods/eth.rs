@@ -1379,8 +1379,8 @@ impl RpcMethod<0> for EthSyncing {
         ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
         (): Self::Params,
     ) -> Result<Self::Ok, ServerError> {
-        let crate::rpc::sync::RPCSyncState { active_syncs } =
-            crate::rpc::sync::SyncState::handle(ctx, ()).await?;
+        let crate::rpc::sync::RPCSyncState { active_syncs, .. } =
+            crate::rpc::sync::SyncState::handle(ctx, (None,)).await?;
         match active_syncs
             .into_iter()
             .rev()
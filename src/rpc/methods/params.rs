@@ -0,0 +1,144 @@
+// Copyright 2019-2024 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::path::Path;
+
+use crate::lotus_json::lotus_json_with_self;
+use crate::networks::NetworkChain;
+use crate::rpc::{ApiPaths, Ctx, Permission, RpcMethod, ServerError};
+use crate::utils::proofs_api::{expected_params_version, missing_params, params_cache_dir};
+use fvm_ipld_blockstore::Blockstore;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Checks whether the proofs parameter cache of the node satisfies the manifest with the given
+/// digest, see [`expected_params_version`], i.e., the node uses that manifest and none of the
+/// parameter files it needs are missing. Useful for auditing a fleet of nodes without
+/// transferring the files.
+pub enum ParamsCheckAgainst {}
+impl RpcMethod<1> for ParamsCheckAgainst {
+    const NAME: &'static str = "Forest.ParamsCheckAgainst";
+    const PARAM_NAMES: [&'static str; 1] = ["manifestDigest"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Read;
+
+    type Params = (String,);
+    type Ok = ParamsCheckResult;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore>,
+        (manifest_digest,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        Ok(check_params_against(
+            &params_cache_dir()?,
+            &ctx.chain_config().network,
+            &manifest_digest,
+        )?)
+    }
+}
+
+/// Returns the version of the proofs parameters the node expects, see
+/// [`expected_params_version`]. Nodes returning the same version use the same manifest.
+pub enum ParamsVersion {}
+impl RpcMethod<0> for ParamsVersion {
+    const NAME: &'static str = "Forest.ParamsVersion";
+    const PARAM_NAMES: [&'static str; 0] = [];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Read;
+
+    type Params = ();
+    type Ok = String;
+
+    async fn handle(_: Ctx<impl Blockstore>, (): Self::Params) -> Result<Self::Ok, ServerError> {
+        Ok(expected_params_version().to_owned())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema)]
+pub struct ParamsCheckResult {
+    /// Whether the cache satisfies the manifest.
+    pub ok: bool,
+    /// Whether the node uses the given manifest.
+    pub manifest_matches: bool,
+    /// Names of the parameter files missing from the cache, or not of the size declared in the
+    /// manifest.
+    pub missing: Vec<String>,
+}
+lotus_json_with_self!(ParamsCheckResult);
+
+fn check_params_against(
+    data_dir: &Path,
+    network: &NetworkChain,
+    digest: &str,
+) -> anyhow::Result<ParamsCheckResult> {
+    let manifest_matches = expected_params_version().eq_ignore_ascii_case(digest);
+    let missing = missing_params(data_dir, network)?;
+    Ok(ParamsCheckResult {
+        ok: manifest_matches && missing.is_empty(),
+        manifest_matches,
+        missing,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lotus_json::HasLotusJson as _;
+    use crate::rpc::reflect::{jsonrpc_types::RequestParameters, Params, RpcMethodExt as _};
+    use crate::utils::proofs_api::test_params::{self, CONTENT};
+    use crate::utils::proofs_api::{manifest_digest, DEFAULT_PARAMETERS};
+
+    #[test]
+    fn params_check_against_round_trip() {
+        let (data_dir, param_dir) = test_params::data_dir();
+        let network = NetworkChain::Calibnet;
+        let hash = manifest_digest(DEFAULT_PARAMETERS).unwrap();
+
+        // The request parameters round-trip through JSON.
+        let serde_json::Value::Array(params) =
+            ParamsCheckAgainst::request_params((hash.clone(),)).unwrap()
+        else {
+            panic!("parameters are passed by position");
+        };
+        let (manifest_hash,) = <(String,) as Params<1>>::parse(
+            Some(RequestParameters::ByPosition(params)),
+            ParamsCheckAgainst::PARAM_NAMES,
+            openrpc_types::ParamStructure::Either,
+            ParamsCheckAgainst::N_REQUIRED_PARAMS,
+        )
+        .unwrap();
+        assert_eq!(manifest_hash, hash);
+
+        let needed = missing_params(data_dir.path(), &network).unwrap();
+        assert!(!needed.is_empty());
+        let (absent, present) = needed.split_first().unwrap();
+        for name in present {
+            std::fs::write(param_dir.join(name), CONTENT).unwrap();
+        }
+
+        let result = check_params_against(data_dir.path(), &network, &manifest_hash).unwrap();
+        assert_eq!(
+            result,
+            ParamsCheckResult {
+                ok: false,
+                manifest_matches: true,
+                missing: vec![absent.clone()],
+            }
+        );
+        // The response round-trips through JSON.
+        let json = result.clone().into_lotus_json_value().unwrap();
+        assert_eq!(
+            ParamsCheckResult::from_lotus_json(serde_json::from_value(json).unwrap()),
+            result
+        );
+
+        std::fs::write(param_dir.join(absent), CONTENT).unwrap();
+        assert!(
+            check_params_against(data_dir.path(), &network, &manifest_hash)
+                .unwrap()
+                .ok
+        );
+        let other = check_params_against(data_dir.path(), &network, &"00".repeat(32)).unwrap();
+        assert!(!other.ok && !other.manifest_matches && other.missing.is_empty());
+    }
+}
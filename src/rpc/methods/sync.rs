@@ -3,13 +3,18 @@
 
 use crate::blocks::{Block, FullTipset, GossipBlock, Tipset};
 use crate::libp2p::{IdentTopic, NetworkMessage, PUBSUB_BLOCK_STR};
-use crate::lotus_json::{lotus_json_with_self, LotusJson};
+use crate::lotus_json::{HasLotusJson, LotusJson};
 use crate::rpc::{ApiPaths, Ctx, Permission, RpcMethod, ServerError};
 use anyhow::{anyhow, Context as _};
 use cid::Cid;
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::to_vec;
 use nunny::{vec as nonempty, Vec as NonEmpty};
+use prometheus_client::{
+    encoding::EncodeLabelSet,
+    metrics::{family::Family, gauge::Gauge},
+    registry::Registry,
+};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -35,6 +40,40 @@ impl RpcMethod<1> for SyncCheckBad {
     }
 }
 
+/// Maximum number of bad blocks returned by a single [`SyncListBad`] call.
+const SYNC_LIST_BAD_MAX_LIMIT: u64 = 1000;
+
+/// Lists the bad blocks, along with the reasons they were marked bad, sorted by CID. The list is
+/// paginated, fetch the next page by offsetting with the number of entries received so far until
+/// fewer than `limit` entries are returned.
+pub enum SyncListBad {}
+impl RpcMethod<2> for SyncListBad {
+    const N_REQUIRED_PARAMS: usize = 0;
+    const NAME: &'static str = "Forest.SyncListBad";
+    const PARAM_NAMES: [&'static str; 2] = ["offset", "limit"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Read;
+
+    type Params = (Option<u64>, Option<u64>);
+    type Ok = Vec<(Cid, String)>;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore>,
+        (offset, limit): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let limit = limit
+            .unwrap_or(SYNC_LIST_BAD_MAX_LIMIT)
+            .min(SYNC_LIST_BAD_MAX_LIMIT);
+        Ok(ctx
+            .bad_blocks
+            .entries()
+            .into_iter()
+            .skip(offset.unwrap_or_default().try_into().unwrap_or(usize::MAX))
+            .take(limit.try_into().unwrap_or(usize::MAX))
+            .collect())
+    }
+}
+
 pub enum SyncMarkBad {}
 impl RpcMethod<1> for SyncMarkBad {
     const NAME: &'static str = "Filecoin.SyncMarkBad";
@@ -49,25 +88,97 @@ impl RpcMethod<1> for SyncMarkBad {
         ctx: Ctx<impl Blockstore>,
         (cid,): Self::Params,
     ) -> Result<Self::Ok, ServerError> {
-        ctx.bad_blocks
-            .put(cid, "Marked bad manually through RPC API".to_string());
+        ctx.bad_blocks.mark_bad(
+            cid,
+            "Marked bad manually through RPC API".to_string(),
+            ctx.chain_store().genesis_block_header().cid(),
+        )?;
         Ok(())
     }
 }
 
+/// Stops all the sync workers and prevents new ones from starting until [`SyncResume`] is called,
+/// e.g., during emergency maintenance. Returns the number of workers stopped, i.e., `0` if they
+/// were already stopped.
+pub enum SyncStopAll {}
+impl RpcMethod<0> for SyncStopAll {
+    const NAME: &'static str = "Forest.SyncStopAll";
+    const PARAM_NAMES: [&'static str; 0] = [];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Admin;
+
+    type Params = ();
+    type Ok = u64;
+
+    async fn handle(ctx: Ctx<impl Blockstore>, (): Self::Params) -> Result<Self::Ok, ServerError> {
+        if !ctx.sync_control.stop_all() {
+            return Ok(0);
+        }
+        let mut state = ctx.sync_state.write();
+        let active = state.stage() != SyncStage::Idle;
+        state.set_stage(SyncStage::Idle);
+        Ok(active.into())
+    }
+}
+
+/// Lets the sync workers stopped by [`SyncStopAll`] start again. Returns whether they were
+/// stopped.
+pub enum SyncResume {}
+impl RpcMethod<0> for SyncResume {
+    const NAME: &'static str = "Forest.SyncResume";
+    const PARAM_NAMES: [&'static str; 0] = [];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Admin;
+
+    type Params = ();
+    type Ok = bool;
+
+    async fn handle(ctx: Ctx<impl Blockstore>, (): Self::Params) -> Result<Self::Ok, ServerError> {
+        Ok(ctx.sync_control.resume())
+    }
+}
+
+/// Identifier of a sync worker, i.e., its position in [`RPCSyncState::active_syncs`].
+pub type WorkerId = u64;
+
 pub enum SyncState {}
-impl RpcMethod<0> for SyncState {
+impl RpcMethod<1> for SyncState {
+    const N_REQUIRED_PARAMS: usize = 0;
     const NAME: &'static str = "Filecoin.SyncState";
-    const PARAM_NAMES: [&'static str; 0] = [];
+    const PARAM_NAMES: [&'static str; 1] = ["workerId"];
     const API_PATHS: ApiPaths = ApiPaths::V1;
     const PERMISSION: Permission = Permission::Read;
 
-    type Params = ();
+    /// State of the given worker only, or of all of them if omitted.
+    type Params = (Option<WorkerId>,);
     type Ok = RPCSyncState;
 
-    async fn handle(ctx: Ctx<impl Blockstore>, (): Self::Params) -> Result<Self::Ok, ServerError> {
-        let active_syncs = nonempty![ctx.sync_state.as_ref().read().clone()];
-        Ok(RPCSyncState { active_syncs })
+    async fn handle(
+        ctx: Ctx<impl Blockstore>,
+        (worker_id,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let active_syncs = {
+            let mut state = ctx.sync_state.write();
+            state.detect_stall(chrono::Utc::now(), crate::chain_sync::SYNC_STALL_THRESHOLD);
+            nonempty![state.clone()]
+        };
+        let seconds_since_head_change = Some(ctx.chain_store().time_since_head_change().as_secs());
+        match worker_id {
+            None => Ok(RPCSyncState {
+                active_syncs,
+                seconds_since_head_change,
+            }),
+            Some(id) => {
+                let state = usize::try_from(id)
+                    .ok()
+                    .and_then(|i| active_syncs.get(i))
+                    .with_context(|| format!("no sync worker with id {id}"))?;
+                Ok(RPCSyncState {
+                    active_syncs: nonempty![state.clone()],
+                    seconds_since_head_change,
+                })
+            }
+        }
     }
 }
 
@@ -123,14 +234,155 @@ impl RpcMethod<1> for SyncSubmitBlock {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
-#[serde(rename_all = "PascalCase")]
+#[derive(Debug, Clone, PartialEq)]
 pub struct RPCSyncState {
-    #[schemars(with = "LotusJson<Vec<crate::chain_sync::SyncState>>")]
-    #[serde(with = "crate::lotus_json")]
     pub active_syncs: NonEmpty<crate::chain_sync::SyncState>,
+    /// Seconds elapsed since the chain head last changed, e.g., to detect a stalled node. Not
+    /// reported by Lotus.
+    pub seconds_since_head_change: Option<u64>,
+}
+
+/// The `SyncState` of Lotus. The worker ids are the positions of the workers.
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(rename = "RPCSyncState")]
+#[serde(rename_all = "PascalCase")]
+pub struct RPCSyncStateLotusJson {
+    active_syncs: NonEmpty<ActiveSyncLotusJson>,
+    /// Number of messages applied by the VM, not tracked by Forest.
+    #[serde(rename = "VMApplied", default)]
+    vm_applied: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seconds_since_head_change: Option<u64>,
+}
+
+/// The `ActiveSync` of Lotus.
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+struct ActiveSyncLotusJson {
+    #[serde(rename = "WorkerID", default)]
+    worker_id: WorkerId,
+    #[schemars(with = "LotusJson<crate::chain_sync::SyncState>")]
+    #[serde(flatten, with = "crate::lotus_json")]
+    state: crate::chain_sync::SyncState,
+}
+
+impl HasLotusJson for RPCSyncState {
+    type LotusJson = RPCSyncStateLotusJson;
+
+    #[cfg(test)]
+    fn snapshots() -> Vec<(serde_json::Value, Self)> {
+        vec![
+            (
+                serde_json::json!({
+                    "ActiveSyncs": [{
+                        "WorkerID": 0,
+                        "Base": null,
+                        "Target": null,
+                        "Stage": 1,
+                        "Height": 0,
+                        "Start": "0001-01-01T00:00:00Z",
+                        "End": "0001-01-01T00:00:00Z",
+                        "Message": "",
+                    }],
+                    "VMApplied": 0,
+                }),
+                Self {
+                    active_syncs: nonempty![Default::default()],
+                    seconds_since_head_change: None,
+                },
+            ),
+            (
+                serde_json::json!({
+                    "ActiveSyncs": [{
+                        "WorkerID": 0,
+                        "Base": null,
+                        "Target": null,
+                        "Stage": 1,
+                        "Height": 0,
+                        "Start": "0001-01-01T00:00:00Z",
+                        "End": "0001-01-01T00:00:00Z",
+                        "Message": "",
+                    }],
+                    "VMApplied": 0,
+                    "SecondsSinceHeadChange": 30,
+                }),
+                Self {
+                    active_syncs: nonempty![Default::default()],
+                    seconds_since_head_change: Some(30),
+                },
+            ),
+        ]
+    }
+
+    fn into_lotus_json(self) -> Self::LotusJson {
+        RPCSyncStateLotusJson {
+            active_syncs: self
+                .active_syncs
+                .into_iter_ne()
+                .enumerate()
+                .map(|(worker_id, state)| ActiveSyncLotusJson {
+                    worker_id: worker_id as WorkerId,
+                    state,
+                })
+                .collect_vec(),
+            vm_applied: 0,
+            seconds_since_head_change: self.seconds_since_head_change,
+        }
+    }
+
+    fn from_lotus_json(lotus_json: Self::LotusJson) -> Self {
+        Self {
+            active_syncs: lotus_json
+                .active_syncs
+                .into_iter_ne()
+                .map(|active_sync| active_sync.state)
+                .collect_vec(),
+            seconds_since_head_change: lotus_json.seconds_since_head_change,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct SyncWorkerLabel {
+    worker: String,
+}
+
+impl RPCSyncState {
+    /// Encodes the state of the sync workers as Prometheus gauges, labeled by worker id, in the
+    /// text exposition format. The stage is encoded as its position in [`SyncStage`].
+    pub fn encode_prometheus(&self) -> anyhow::Result<String> {
+        let height = Family::<SyncWorkerLabel, Gauge>::default();
+        let target_height = Family::<SyncWorkerLabel, Gauge>::default();
+        let gap = Family::<SyncWorkerLabel, Gauge>::default();
+        let stage = Family::<SyncWorkerLabel, Gauge>::default();
+        for (worker, state) in self.active_syncs.iter().enumerate() {
+            let label = SyncWorkerLabel {
+                worker: worker.to_string(),
+            };
+            let target = state
+                .target()
+                .as_ref()
+                .map(|ts| ts.epoch())
+                .unwrap_or_default();
+            height.get_or_create(&label).set(state.epoch());
+            target_height.get_or_create(&label).set(target);
+            gap.get_or_create(&label).set(target - state.epoch());
+            stage.get_or_create(&label).set(state.stage() as i64);
+        }
+
+        let mut registry = Registry::default();
+        registry.register("sync_height", "Epoch reached by the sync worker", height);
+        registry.register(
+            "sync_target_height",
+            "Epoch of the tipset the sync worker is syncing to",
+            target_height,
+        );
+        registry.register("sync_gap", "Epochs left to sync by the sync worker", gap);
+        registry.register("sync_stage", "Stage of the sync worker", stage);
+        let mut metrics = String::new();
+        prometheus_client::encoding::text::encode(&mut metrics, &registry)?;
+        Ok(metrics)
+    }
 }
-lotus_json_with_self!(RPCSyncState);
 
 #[cfg(test)]
 mod tests {
@@ -140,7 +392,7 @@ mod tests {
     use crate::blocks::RawBlockHeader;
     use crate::blocks::{CachingBlockHeader, Tipset};
     use crate::chain::ChainStore;
-    use crate::chain_sync::{SyncConfig, SyncStage};
+    use crate::chain_sync::{SyncConfig, SyncStage, SyncStallReason};
     use crate::db::MemoryDB;
     use crate::key_management::{KeyStore, KeyStoreConfig};
     use crate::libp2p::NetworkMessage;
@@ -151,6 +403,8 @@ mod tests {
     use crate::shim::address::Address;
     use crate::state_manager::StateManager;
     use crate::utils::encoding::from_slice_with_fallback;
+    use cid::multihash::{Code::Blake2b256, MultihashDigest as _};
+    use fvm_ipld_encoding::DAG_CBOR;
     use tokio::sync::mpsc;
     use tokio::{sync::RwLock, task::JoinSet};
 
@@ -220,6 +474,7 @@ mod tests {
             keystore: Arc::new(RwLock::new(KeyStore::new(KeyStoreConfig::Memory).unwrap())),
             mpool: Arc::new(pool),
             bad_blocks: Default::default(),
+            sync_control: Default::default(),
             sync_state: Arc::new(parking_lot::RwLock::new(Default::default())),
             eth_event_handler: Arc::new(EthEventHandler::new()),
             network_send,
@@ -249,21 +504,287 @@ mod tests {
         assert_eq!(reason, "Marked bad manually through RPC API");
     }
 
+    #[tokio::test]
+    async fn mark_bad_rejects_genesis() {
+        let (ctx, _) = ctx();
+
+        let genesis = *ctx.chain_store().genesis_block_header().cid();
+        SyncMarkBad::handle(ctx.clone(), (genesis,))
+            .await
+            .unwrap_err();
+        assert_eq!(ctx.bad_blocks.peek(&genesis), None);
+
+        let cid = "bafy2bzacea3wsdh6y3a36tb3skempjoxqpuyompjbmfeyf34fi3uy6uue42v4"
+            .parse::<Cid>()
+            .unwrap();
+        SyncMarkBad::handle(ctx.clone(), (cid,)).await.unwrap();
+        assert!(ctx.bad_blocks.peek(&cid).is_some());
+    }
+
+    #[tokio::test]
+    async fn list_bad() {
+        let (ctx, _) = ctx();
+
+        let mut bad: Vec<_> = (0..5u8)
+            .map(|i| {
+                let cid = Cid::new_v1(DAG_CBOR, Blake2b256.digest(&[i]));
+                (cid, format!("bad block {i}"))
+            })
+            .collect();
+        for (cid, reason) in &bad {
+            ctx.bad_blocks
+                .mark_bad(
+                    *cid,
+                    reason.clone(),
+                    ctx.chain_store().genesis_block_header().cid(),
+                )
+                .unwrap();
+        }
+        bad.sort();
+
+        let all = SyncListBad::handle(ctx.clone(), (None, None))
+            .await
+            .unwrap();
+        assert_eq!(all, bad);
+
+        let mut paginated = vec![];
+        loop {
+            let page = SyncListBad::handle(ctx.clone(), (Some(paginated.len() as u64), Some(2)))
+                .await
+                .unwrap();
+            let done = page.len() < 2;
+            paginated.extend(page);
+            if done {
+                break;
+            }
+        }
+        assert_eq!(paginated, bad);
+
+        // The response round-trips through JSON.
+        let json = all.clone().into_lotus_json_value().unwrap();
+        assert_eq!(
+            <Vec<(Cid, String)>>::from_lotus_json(serde_json::from_value(json).unwrap()),
+            all
+        );
+    }
+
     #[tokio::test]
     async fn sync_state_test() {
         let (ctx, _) = ctx();
 
         let st_copy = ctx.sync_state.clone();
 
-        let ret = SyncState::handle(ctx.clone(), ()).await.unwrap();
+        let ret = SyncState::handle(ctx.clone(), (None,)).await.unwrap();
         assert_eq!(ret.active_syncs, nonempty![st_copy.as_ref().read().clone()]);
 
         // update cloned state
         st_copy.write().set_stage(SyncStage::Messages);
         st_copy.write().set_epoch(4);
 
-        let ret = SyncState::handle(ctx.clone(), ()).await.unwrap();
+        let ret = SyncState::handle(ctx.clone(), (None,)).await.unwrap();
 
         assert_eq!(ret.active_syncs, nonempty![st_copy.as_ref().read().clone()]);
     }
+
+    #[tokio::test]
+    async fn sync_state_worker_test() {
+        let (ctx, _) = ctx();
+        ctx.sync_state.write().set_epoch(4);
+
+        let ret = SyncState::handle(ctx.clone(), (Some(0),)).await.unwrap();
+        assert_eq!(
+            ret.active_syncs,
+            nonempty![ctx.sync_state.as_ref().read().clone()]
+        );
+
+        SyncState::handle(ctx.clone(), (Some(1),))
+            .await
+            .unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn sync_state_stalled_worker() {
+        let (ctx, _) = ctx();
+        let cid = "bafy2bzacea3wsdh6y3a36tb3skempjoxqpuyompjbmfeyf34fi3uy6uue42v4"
+            .parse::<Cid>()
+            .unwrap();
+        {
+            let mut state = ctx.sync_state.write();
+            state.set_stage(SyncStage::Messages);
+            state.record_progress(chrono::Utc::now() - chrono::Duration::hours(1));
+            state.set_stall_cause(SyncStallReason::BadBlock { cid });
+        }
+
+        let ret = SyncState::handle(ctx.clone(), (Some(0),)).await.unwrap();
+        assert_eq!(
+            ret.active_syncs.first().stall_reason(),
+            Some(&SyncStallReason::BadBlock { cid })
+        );
+        let json = ret.into_lotus_json_value().unwrap();
+        assert_eq!(
+            json["ActiveSyncs"][0]["StallReason"],
+            serde_json::json!({ "Reason": "BadBlock", "Cid": { "/": cid.to_string() } })
+        );
+
+        // Once the worker makes progress again, it is not stalled anymore.
+        ctx.sync_state.write().set_epoch(4);
+        let ret = SyncState::handle(ctx.clone(), (Some(0),)).await.unwrap();
+        assert_eq!(ret.active_syncs.first().stall_reason(), None);
+    }
+
+    #[tokio::test]
+    async fn sync_stop_all_resume() {
+        let (ctx, _) = ctx();
+        ctx.sync_state.write().set_stage(SyncStage::Messages);
+
+        let stopped = SyncStopAll::handle(ctx.clone(), ()).await.unwrap();
+        assert_eq!(stopped, 1);
+        assert!(ctx.sync_control.is_stopped());
+        // Stopping again is a no-op.
+        assert_eq!(SyncStopAll::handle(ctx.clone(), ()).await.unwrap(), 0);
+
+        // No worker is active anymore.
+        let ret = SyncState::handle(ctx.clone(), (None,)).await.unwrap();
+        assert!(ret
+            .active_syncs
+            .iter()
+            .all(|state| state.stage() == SyncStage::Idle));
+        let ret = RPCSyncState::from_lotus_json(
+            serde_json::from_value(ret.into_lotus_json_value().unwrap()).unwrap(),
+        );
+        assert!(ret
+            .active_syncs
+            .iter()
+            .all(|state| state.stage() == SyncStage::Idle));
+
+        assert!(SyncResume::handle(ctx.clone(), ()).await.unwrap());
+        assert!(!ctx.sync_control.is_stopped());
+        // Resuming again is a no-op.
+        assert!(!SyncResume::handle(ctx.clone(), ()).await.unwrap());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sync_state_head_change_test() {
+        let (ctx, _) = ctx();
+        let genesis = Arc::new(Tipset::from(ctx.chain_store().genesis_block_header()));
+
+        // Advance the head.
+        ctx.chain_store()
+            .set_heaviest_tipset(genesis.clone())
+            .unwrap();
+        let ret = SyncState::handle(ctx.clone(), (None,)).await.unwrap();
+        assert_eq!(ret.seconds_since_head_change, Some(0));
+
+        // Stall the head, setting the same tipset again is not a change.
+        tokio::time::advance(std::time::Duration::from_millis(1100)).await;
+        ctx.chain_store().set_heaviest_tipset(genesis).unwrap();
+        let ret = SyncState::handle(ctx.clone(), (None,)).await.unwrap();
+        assert_eq!(ret.seconds_since_head_change, Some(1));
+    }
+
+    #[test]
+    fn sync_state_prometheus() {
+        let tipset = |epoch| {
+            Arc::new(Tipset::from(CachingBlockHeader::new(RawBlockHeader {
+                miner_address: Address::new_id(0),
+                epoch,
+                ..Default::default()
+            })))
+        };
+        let mut state = crate::chain_sync::SyncState::default();
+        state.init(tipset(10), tipset(100));
+        state.set_stage(SyncStage::Messages);
+        state.set_epoch(40);
+
+        let metrics = RPCSyncState {
+            active_syncs: nonempty![state],
+            seconds_since_head_change: None,
+        }
+        .encode_prometheus()
+        .unwrap();
+        for sample in [
+            r#"sync_height{worker="0"} 40"#,
+            r#"sync_target_height{worker="0"} 100"#,
+            r#"sync_gap{worker="0"} 60"#,
+            r#"sync_stage{worker="0"} 3"#,
+        ] {
+            assert!(
+                metrics.lines().any(|line| line == sample),
+                "{sample} not in {metrics}"
+            );
+        }
+    }
+
+    #[test]
+    fn sync_state_snapshots() {
+        crate::lotus_json::assert_all_snapshots::<RPCSyncState>();
+    }
+
+    #[test]
+    fn sync_state_lotus_fixture() {
+        let fixture: serde_json::Value =
+            serde_json::from_str(include_str!("sync-fixtures/sync_state.json")).unwrap();
+        let state = RPCSyncState::from_lotus_json(serde_json::from_value(fixture.clone()).unwrap());
+
+        let workers: Vec<_> = state.active_syncs.iter().collect();
+        let [complete, idle] = workers.as_slice() else {
+            panic!("expected two workers: {state:?}");
+        };
+        assert_eq!(complete.stage(), SyncStage::Complete);
+        assert!(complete.base().is_some() && complete.target().is_some());
+        assert!(complete.get_elapsed_time().is_some());
+        assert_eq!(idle.stage(), SyncStage::Idle);
+        assert!(idle.base().is_none() && idle.target().is_none());
+        assert!(idle.get_elapsed_time().is_none());
+
+        // Serialized back with the same fields and values as Lotus, Forest extensions aside.
+        let mut json = state.into_lotus_json_value().unwrap();
+        for active_sync in json["ActiveSyncs"].as_array_mut().unwrap() {
+            let active_sync = active_sync.as_object_mut().unwrap();
+            active_sync.remove("TargetWeight");
+            active_sync.remove("RemainingTipsets");
+            active_sync.remove("StageDurations");
+        }
+        assert_eq!(json, fixture);
+    }
+
+    #[test]
+    fn sync_mark_bad_lotus_fixture() {
+        use crate::rpc::reflect::{jsonrpc_types::RequestParameters, Params};
+
+        let request: serde_json::Value =
+            serde_json::from_str(include_str!("sync-fixtures/sync_mark_bad.json")).unwrap();
+        assert_eq!(request["method"], SyncMarkBad::NAME);
+        let params: RequestParameters = serde_json::from_value(request["params"].clone()).unwrap();
+        let (cid,) = <(Cid,) as Params<1>>::parse(
+            Some(params),
+            SyncMarkBad::PARAM_NAMES,
+            openrpc_types::ParamStructure::Either,
+            SyncMarkBad::N_REQUIRED_PARAMS,
+        )
+        .unwrap();
+        assert_eq!(
+            cid.to_string(),
+            "bafy2bzacea3wsdh6y3a36tb3skempjoxqpuyompjbmfeyf34fi3uy6uue42v4"
+        );
+        // Lotus returns `null` for methods without a result.
+        assert_eq!(().into_lotus_json_value().unwrap(), serde_json::Value::Null);
+    }
+
+    #[test]
+    fn sync_state_params_backward_compat() {
+        use crate::rpc::reflect::{jsonrpc_types::RequestParameters, Params};
+
+        // Callers not aware of the worker id keep getting the state of all the workers
+        for params in [None, Some(RequestParameters::ByPosition(vec![]))] {
+            let (worker_id,) = <(Option<WorkerId>,) as Params<1>>::parse(
+                params,
+                SyncState::PARAM_NAMES,
+                openrpc_types::ParamStructure::Either,
+                SyncState::N_REQUIRED_PARAMS,
+            )
+            .unwrap();
+            assert_eq!(worker_id, None);
+        }
+    }
 }
@@ -39,6 +39,7 @@ macro_rules! for_each_method {
 
         // beacon vertical
         $callback!(crate::rpc::beacon::BeaconGetEntry);
+        $callback!(crate::rpc::beacon::BeaconGetEntryInfo);
 
         // chain vertical
         $callback!(crate::rpc::chain::ChainExport);
@@ -213,10 +214,17 @@ macro_rules! for_each_method {
         $callback!(crate::rpc::state::StateWaitMsg);
         $callback!(crate::rpc::state::StateWaitMsgV0);
 
+        // params vertical
+        $callback!(crate::rpc::params::ParamsCheckAgainst);
+        $callback!(crate::rpc::params::ParamsVersion);
+
         // sync vertical
         $callback!(crate::rpc::sync::SyncCheckBad);
+        $callback!(crate::rpc::sync::SyncListBad);
         $callback!(crate::rpc::sync::SyncMarkBad);
+        $callback!(crate::rpc::sync::SyncResume);
         $callback!(crate::rpc::sync::SyncState);
+        $callback!(crate::rpc::sync::SyncStopAll);
         $callback!(crate::rpc::sync::SyncSubmitBlock);
 
         // wallet vertical
@@ -335,6 +343,7 @@ mod methods {
     pub mod msig;
     pub mod net;
     pub mod node;
+    pub mod params;
     pub mod state;
     pub mod sync;
     pub mod wallet;
@@ -382,6 +391,7 @@ pub struct RPCState<DB> {
     pub state_manager: Arc<crate::state_manager::StateManager<DB>>,
     pub mpool: Arc<crate::message_pool::MessagePool<crate::message_pool::MpoolRpcProvider<DB>>>,
     pub bad_blocks: Arc<crate::chain_sync::BadBlockCache>,
+    pub sync_control: Arc<crate::chain_sync::SyncControl>,
     pub sync_state: Arc<parking_lot::RwLock<crate::chain_sync::SyncState>>,
     pub eth_event_handler: Arc<EthEventHandler>,
     pub network_send: flume::Sender<crate::libp2p::NetworkMessage>,
@@ -384,3 +384,20 @@ impl From<fil_actor_miner_state::v15::SectorPreCommitInfo> for SectorPreCommitIn
         }
     }
 }
+
+impl SectorPreCommitInfo {
+    /// Checks that the sealed and (optional) unsealed CIDs are Filecoin piece
+    /// commitments, i.e. that they use the `fil-commitment-*` codecs and the
+    /// matching multihash functions, rather than arbitrary CIDs. Not enforced by the RPC
+    /// handlers, which accept the same inputs as Lotus.
+    #[allow(dead_code)]
+    pub fn validate(&self) -> anyhow::Result<()> {
+        fvm_shared4::commcid::cid_to_replica_commitment_v1(&self.sealed_cid)
+            .map_err(|e| anyhow::anyhow!("invalid sealed CID {}: {e}", self.sealed_cid))?;
+        if let Some(unsealed_cid) = &self.unsealed_cid {
+            fvm_shared4::commcid::cid_to_data_commitment_v1(unsealed_cid)
+                .map_err(|e| anyhow::anyhow!("invalid unsealed CID {unsealed_cid}: {e}"))?;
+        }
+        Ok(())
+    }
+}
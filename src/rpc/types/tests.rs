@@ -37,3 +37,47 @@ fn test_api_tipset_key_inner(cids: Vec<Cid>) {
         .unwrap_or_default();
     assert_eq!(cids_from_api_ts, cids);
 }
+
+fn sector_pre_commit_info(sealed_cid: Cid, unsealed_cid: Option<Cid>) -> SectorPreCommitInfo {
+    SectorPreCommitInfo {
+        seal_proof: fvm_shared4::sector::RegisteredSealProof::StackedDRG32GiBV1P1.into(),
+        sector_number: 1,
+        sealed_cid,
+        seal_rand_epoch: 0,
+        deal_ids: vec![],
+        expiration: 0,
+        unsealed_cid,
+    }
+}
+
+#[test]
+fn test_sector_pre_commit_info_validate_commitment_cids() {
+    use fvm_shared4::commcid::{
+        commitment_to_cid, FIL_COMMITMENT_SEALED, FIL_COMMITMENT_UNSEALED,
+        POSEIDON_BLS12_381_A1_FC1, SHA2_256_TRUNC254_PADDED,
+    };
+
+    let sealed_cid =
+        commitment_to_cid(FIL_COMMITMENT_SEALED, POSEIDON_BLS12_381_A1_FC1, &[1; 32]).unwrap();
+    let unsealed_cid =
+        commitment_to_cid(FIL_COMMITMENT_UNSEALED, SHA2_256_TRUNC254_PADDED, &[2; 32]).unwrap();
+
+    sector_pre_commit_info(sealed_cid, None).validate().unwrap();
+    sector_pre_commit_info(sealed_cid, Some(unsealed_cid))
+        .validate()
+        .unwrap();
+    // Swapped commitments must be rejected.
+    sector_pre_commit_info(unsealed_cid, Some(sealed_cid))
+        .validate()
+        .unwrap_err();
+}
+
+#[test]
+fn test_sector_pre_commit_info_validate_rejects_dag_cbor() {
+    use crate::utils::cid::CidCborExt as _;
+
+    let dag_cbor_cid = Cid::from_cbor_blake2b256(&"arbitrary").unwrap();
+    sector_pre_commit_info(dag_cbor_cid, None)
+        .validate()
+        .unwrap_err();
+}
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use crate::shim::version::NetworkVersion;
-use anyhow::bail;
+use anyhow::{bail, ensure};
 use cid::Cid;
 use fvm_ipld_encoding::repr::{Deserialize_repr, Serialize_repr};
 use fvm_shared2::sector::{
@@ -16,8 +16,8 @@ pub use fvm_shared3::sector::{
 };
 pub use fvm_shared4::sector::{
     PoStProof as PoStProofV4, RegisteredPoStProof as RegisteredPoStProofV4,
-    RegisteredSealProof as RegisteredSealProofV4, SectorInfo as SectorInfoV4,
-    SectorSize as SectorSizeV4,
+    RegisteredSealProof as RegisteredSealProofV4, SealVerifyInfo as SealVerifyInfoV4,
+    SectorInfo as SectorInfoV4, SectorSize as SectorSizeV4,
 };
 use num_derive::FromPrimitive;
 use serde::{Deserialize, Serialize};
@@ -219,6 +219,64 @@ impl quickcheck::Arbitrary for ExtendedSectorInfo {
     }
 }
 
+/// Length, in bytes, of a single Groth16 SNARK proof.
+const SNARK_PROOF_LEN: usize = 192;
+
+/// Represents a shim over `SealVerifyInfo` from `fvm_shared`, the information needed to verify a
+/// seal proof.
+#[derive(
+    Eq, PartialEq, Debug, Clone, derive_more::From, derive_more::Into, Serialize, Deserialize,
+)]
+pub struct SealVerifyInfo(SealVerifyInfoV4);
+
+impl SealVerifyInfo {
+    /// Checks that the length of the proof is the one expected for its registered proof type,
+    /// i.e., one SNARK proof per partition of the sector, so that malformed verify info is
+    /// rejected before it reaches the proof verifier. The length of the non-interactive proofs is
+    /// not fixed by their type, so it is not checked.
+    pub fn validate_proof_len(&self) -> anyhow::Result<()> {
+        use fvm_shared4::sector::RegisteredSealProof as SealProof;
+        let partitions = match self.0.registered_proof {
+            SealProof::StackedDRG2KiBV1
+            | SealProof::StackedDRG2KiBV1P1
+            | SealProof::StackedDRG2KiBV1P1_Feat_SyntheticPoRep
+            | SealProof::StackedDRG8MiBV1
+            | SealProof::StackedDRG8MiBV1P1
+            | SealProof::StackedDRG8MiBV1P1_Feat_SyntheticPoRep
+            | SealProof::StackedDRG512MiBV1
+            | SealProof::StackedDRG512MiBV1P1
+            | SealProof::StackedDRG512MiBV1P1_Feat_SyntheticPoRep => 1,
+            SealProof::StackedDRG32GiBV1
+            | SealProof::StackedDRG32GiBV1P1
+            | SealProof::StackedDRG32GiBV1P1_Feat_SyntheticPoRep
+            | SealProof::StackedDRG64GiBV1
+            | SealProof::StackedDRG64GiBV1P1
+            | SealProof::StackedDRG64GiBV1P1_Feat_SyntheticPoRep => 10,
+            SealProof::StackedDRG2KiBV1P2_Feat_NiPoRep
+            | SealProof::StackedDRG8MiBV1P2_Feat_NiPoRep
+            | SealProof::StackedDRG512MiBV1P2_Feat_NiPoRep
+            | SealProof::StackedDRG32GiBV1P2_Feat_NiPoRep
+            | SealProof::StackedDRG64GiBV1P2_Feat_NiPoRep => return Ok(()),
+            SealProof::Invalid(_) => bail!("Invalid seal proof type {:?}", self.0.registered_proof),
+        };
+        let expected = partitions * SNARK_PROOF_LEN;
+        ensure!(
+            self.0.proof.len() == expected,
+            "Seal proof of {} bytes, expected {expected} for {:?}",
+            self.0.proof.len(),
+            self.0.registered_proof
+        );
+        Ok(())
+    }
+}
+
+impl Deref for SealVerifyInfo {
+    type Target = SealVerifyInfoV4;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, derive_more::Into)]
 pub struct RegisteredPoStProof(RegisteredPoStProofV4);
 
@@ -271,7 +329,18 @@ impl From<RegisteredPoStProofV4> for RegisteredPoStProof {
 }
 
 /// `SectorSize` indicates one of a set of possible sizes in the network.
-#[derive(Clone, Debug, PartialEq, Eq, Copy, FromPrimitive, Serialize_repr, Deserialize_repr)]
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Copy,
+    FromPrimitive,
+    Serialize_repr,
+    Deserialize_repr,
+)]
 #[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
 #[repr(u64)]
 pub enum SectorSize {
@@ -313,6 +382,19 @@ macro_rules! sector_size_conversion {
 
 sector_size_conversion!(SectorSizeV2, SectorSizeV3, SectorSizeV4);
 
+impl SectorSize {
+    /// Returns the size of the sector in bytes.
+    pub fn as_bytes(self) -> u64 {
+        self as u64
+    }
+
+    /// Returns the sector size of the given number of bytes, or `None` if no sector has that
+    /// size.
+    pub fn from_bytes(bytes: u64) -> Option<Self> {
+        num::FromPrimitive::from_u64(bytes)
+    }
+}
+
 #[derive(
     serde::Serialize,
     serde::Deserialize,
@@ -410,4 +492,67 @@ mod tests {
 
         assert_eq!(shimmed_deser as u64, orig_deser as u64);
     }
+
+    #[test]
+    fn sector_size_bytes_round_trip() {
+        use crate::shim::sector::SectorSize;
+
+        for size in [
+            SectorSize::_2KiB,
+            SectorSize::_8MiB,
+            SectorSize::_512MiB,
+            SectorSize::_32GiB,
+            SectorSize::_64GiB,
+        ] {
+            assert_eq!(SectorSize::from_bytes(size.as_bytes()), Some(size));
+        }
+        assert_eq!(SectorSize::_32GiB.as_bytes(), 34359738368);
+        assert_eq!(SectorSize::from_bytes(0), None);
+        assert_eq!(SectorSize::from_bytes(4096), None);
+        assert_eq!(SectorSize::from_bytes(u64::MAX), None);
+    }
+
+    fn seal_verify_info(
+        registered_proof: fvm_shared4::sector::RegisteredSealProof,
+        proof_len: usize,
+    ) -> crate::shim::sector::SealVerifyInfo {
+        use fvm_shared4::randomness::Randomness;
+
+        let cid = cid::Cid::default();
+        fvm_shared4::sector::SealVerifyInfo {
+            registered_proof,
+            sector_id: fvm_shared4::sector::SectorID {
+                miner: 1000,
+                number: 1,
+            },
+            deal_ids: vec![],
+            randomness: Randomness(vec![0; 32]),
+            interactive_randomness: Randomness(vec![0; 32]),
+            proof: vec![0; proof_len],
+            sealed_cid: cid,
+            unsealed_cid: cid,
+        }
+        .into()
+    }
+
+    #[test]
+    fn seal_verify_info_proof_len() {
+        use fvm_shared4::sector::RegisteredSealProof;
+
+        seal_verify_info(RegisteredSealProof::StackedDRG32GiBV1P1, 1920)
+            .validate_proof_len()
+            .unwrap();
+        seal_verify_info(RegisteredSealProof::StackedDRG2KiBV1P1, 192)
+            .validate_proof_len()
+            .unwrap();
+        seal_verify_info(RegisteredSealProof::StackedDRG32GiBV1P1, 192)
+            .validate_proof_len()
+            .unwrap_err();
+        seal_verify_info(RegisteredSealProof::StackedDRG2KiBV1P1, 0)
+            .validate_proof_len()
+            .unwrap_err();
+        seal_verify_info(RegisteredSealProof::Invalid(42), 192)
+            .validate_proof_len()
+            .unwrap_err();
+    }
 }
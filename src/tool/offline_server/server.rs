@@ -130,6 +130,7 @@ pub async fn start_offline_server(
         keystore: Arc::new(RwLock::new(keystore)),
         mpool: Arc::new(message_pool),
         bad_blocks: Default::default(),
+        sync_control: Default::default(),
         sync_state: Arc::new(parking_lot::RwLock::new(Default::default())),
         eth_event_handler: Arc::new(EthEventHandler::new()),
         network_send,
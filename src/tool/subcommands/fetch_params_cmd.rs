@@ -1,10 +1,23 @@
 // Copyright 2019-2024 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use std::path::PathBuf;
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use crate::shim::sector::SectorSize;
-use crate::utils::proofs_api::{get_params_default, SectorSizeOpt};
+use crate::utils::proofs_api::{
+    build_manifest, diff_manifests, enforce_quota, fetch_manifest, get_params, manifest_digest,
+    manifest_entry, manifest_sector_sizes, migrate_cache, open_verified, supported_proofs,
+    verify_all, verify_params, verify_stdin, Credentials, DownloadBudget, LocalParamStore,
+    ManifestSignature, MismatchPolicy, ParamFetchConfig, ParamFetchOutcome, ParamProvenance,
+    ParamProvenanceLog, ParamStore, SectorSizeOpt, DEFAULT_PARAMETERS,
+};
+use anyhow::Context as _;
+use tokio_util::compat::TokioAsyncWriteCompatExt as _;
 
 use crate::cli::subcommands::cli_error_and_die;
 use crate::cli_shared::read_config;
@@ -18,38 +31,445 @@ pub struct FetchCommands {
     /// Download only verification keys
     #[arg(short, long)]
     keys: bool,
+    /// Download only the verification keys of the given sector size, along with the ones not
+    /// bound to a size, e.g., for a node verifying the proofs of a single size
+    #[arg(long, conflicts_with_all = ["all", "keys", "params_size"])]
+    keys_for_size: Option<String>,
     /// Print out download location instead of downloading files
     #[arg(short, long)]
     dry_run: bool,
-    /// Size in bytes
+    /// Stop downloading once this many bytes in total have been transferred
+    #[arg(long)]
+    download_budget: Option<u64>,
+    /// Number of ranges each parameter file is split into and downloaded concurrently, if the
+    /// server supports range requests
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+    download_chunks: u16,
+    /// Optional read-only directory searched for valid parameter files before the cache
+    /// directory. Files found there are linked into the cache directory instead of downloaded
+    #[arg(long)]
+    base_dir: Option<PathBuf>,
+    /// Optional directory to keep the parameter files in instead of the cache directory, e.g., a
+    /// mounted network share. Files are downloaded to the cache directory, then copied there
+    #[arg(long)]
+    store_dir: Option<PathBuf>,
+    /// Optional file containing the bearer token sent along the requests to the IPFS gateway,
+    /// e.g., a private mirror. Also read from `FOREST_PARAMS_BEARER_TOKEN`
+    #[arg(long, conflicts_with = "basic_auth_file")]
+    bearer_token_file: Option<PathBuf>,
+    /// Optional file containing the `user[:password]` sent as HTTP basic authentication along the
+    /// requests to the IPFS gateway. Also read from `FOREST_PARAMS_BASIC_AUTH`
+    #[arg(long)]
+    basic_auth_file: Option<PathBuf>,
+    /// Optional local address to bind the downloads to. Only servers of the same address family
+    /// are connected to, e.g., `0.0.0.0` restricts the downloads to IPv4
+    #[arg(long)]
+    local_address: Option<std::net::IpAddr>,
+    /// Record the fetched parameter files in `params.lock` in the cache directory
+    #[arg(long, conflicts_with = "locked")]
+    write_lockfile: bool,
+    /// Refuse to fetch parameter files whose CID differs from `params.lock`
+    #[arg(long)]
+    locked: bool,
+    /// Keep the parameter files fetched successfully when others fail, and list the failed ones
+    #[arg(long)]
+    best_effort: bool,
+    /// Trust the parameter files already present and larger than this many bytes without
+    /// verifying their digests. Smaller files are always verified
+    #[arg(long)]
+    trust_params_above: Option<u64>,
+    /// Give up after this many seconds, keeping the parameter files fetched by then
+    #[arg(long)]
+    deadline: Option<u64>,
+    /// Also fetch the parameter files marked optional in the manifest
+    #[arg(long)]
+    include_optional: bool,
+    /// Accept downloads shorter than the length announced by the server, as long as they match
+    /// the manifest digest
+    #[arg(long)]
+    lenient_content_length: bool,
+    /// Connect to the download server once before fetching the parameter files in parallel, so
+    /// that they reuse the connection
+    #[arg(long)]
+    warm_up: bool,
+    /// Once fetched, evict the least recently used parameter files not needed by the selected
+    /// sector size until the parameter files take at most this many bytes
+    #[arg(long)]
+    quota: Option<u64>,
+    /// Move parameter files not matching the manifest aside instead of deleting them
+    #[arg(long)]
+    quarantine_corrupt: bool,
+    /// Host the parameter files are trusted to be downloaded from, can be repeated. The files
+    /// downloaded from other hosts are still verified, but are listed once fetched
+    #[arg(long = "trusted-source")]
+    trusted_sources: Vec<String>,
+    /// Hash the parameter files on a dedicated pool of this many threads, rather than on the
+    /// shared blocking pool
+    #[arg(long, value_parser = clap::value_parser!(u16).range(1..))]
+    hash_threads: Option<u16>,
+    /// Optional parameter manifest to use instead of the bundled one
+    #[arg(long, conflicts_with = "manifest_url")]
+    manifest: Option<PathBuf>,
+    /// Optional URL to fetch the parameter manifest from instead of using the bundled one
+    #[arg(long, requires = "manifest_hash")]
+    manifest_url: Option<url::Url>,
+    /// Hex-encoded BLAKE2b-256 hash the manifest fetched from `--manifest-url` must match
+    #[arg(long, requires = "manifest_url")]
+    manifest_hash: Option<String>,
+    /// Hex-encoded detached BLS signature of the manifest
+    #[arg(long, requires = "manifest_public_key")]
+    manifest_signature: Option<String>,
+    /// Hex-encoded BLS public key of the manifest signer
+    #[arg(long, requires = "manifest_signature")]
+    manifest_public_key: Option<String>,
+    /// Sector size in bytes. Its proofs gen parameters are downloaded along with all the
+    /// verification keys
     params_size: Option<String>,
     /// Optional TOML file containing forest daemon configuration
     #[arg(short, long)]
     pub config: Option<PathBuf>,
+    /// Inspect the parameter files or the manifest instead of fetching the parameter files. The
+    /// options above apply, and go before the subcommand
+    #[command(subcommand)]
+    command: Option<FetchParamsCommands>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum FetchParamsCommands {
+    /// Verify the downloaded parameter files selected by the sector size options without fetching
+    /// anything, and print a JSON report
+    Verify {
+        /// Hash this many parameter files at once and show the total number of bytes hashed
+        #[arg(long, value_parser = clap::value_parser!(u16).range(1..))]
+        concurrency: Option<u16>,
+    },
+    /// Verify the parameter file with the given name in the cache directory against the manifest,
+    /// and print whether it matches
+    VerifyParam {
+        /// Name of the parameter file, e.g., `v28-stacked-proof-of-replication-[...].vk`
+        name: String,
+    },
+    /// Verify the parameter file with the given name piped on stdin against the manifest, and
+    /// print whether it matches
+    VerifyStdin {
+        /// Name of the parameter file
+        name: String,
+    },
+    /// Write the parameter file with the given name from the cache directory to stdout, once
+    /// verified against the manifest
+    Cat {
+        /// Name of the parameter file
+        name: String,
+    },
+    /// List the sector sizes whose verification files are all present and valid in the cache
+    /// directory, i.e., whose proofs can be verified
+    SupportedSizes,
+    /// List the sector sizes covered by the parameter manifest
+    ListSizes,
+    /// Print the changes from the given older parameter manifest to the one in use as JSON
+    DiffManifest {
+        /// Path to the older parameter manifest
+        old: PathBuf,
+    },
+    /// Print a manifest of the parameter files in the given directory. CIDs and sector sizes are
+    /// taken from the manifest entries with the same name
+    BuildManifest {
+        /// Directory containing the parameter files
+        dir: PathBuf,
+    },
+    /// Print the digest of the parameter manifest, independent of its formatting
+    PrintDigest,
+    /// Rename the parameter files of the cache directory according to the given JSON object,
+    /// mapping the old names to the new ones, if they are valid under their new names
+    MigrateCache {
+        /// Path to the JSON object of renames
+        renames: PathBuf,
+    },
+    /// Print the effective fetch configuration as JSON, including the settings made through
+    /// environment variables. Credentials are redacted
+    PrintConfig,
 }
 
 impl FetchCommands {
     pub async fn run(self) -> anyhow::Result<()> {
+        let manifest = match (&self.manifest, &self.manifest_url, &self.manifest_hash) {
+            (Some(path), _, _) => std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read manifest {}", path.display()))?,
+            (None, Some(url), Some(hash)) => fetch_manifest(url.clone(), hash).await?,
+            _ => DEFAULT_PARAMETERS.to_owned(),
+        };
+
+        if let Some(command) = &self.command {
+            return self.run_command(command, &manifest).await;
+        }
+
+        let data_dir = self.data_dir()?;
+        let sizes = self.sector_sizes()?;
+        let provenance = Arc::new(ParamProvenanceLog::default());
+        let fetch_config = ParamFetchConfig {
+            provenance: Some(provenance.clone()),
+            ..self.fetch_config()?
+        };
+        let outcome = get_params(&data_dir, &manifest, sizes, self.dry_run, &fetch_config).await?;
+        for (name, provenance) in provenance.entries() {
+            if let ParamProvenance::Downloaded {
+                host,
+                trusted: false,
+                verified,
+            } = provenance
+            {
+                let verified = if verified {
+                    "checksum-verified"
+                } else {
+                    "not verified"
+                };
+                println!("{name}: fetched from untrusted source {host}, {verified}");
+            }
+        }
+        if let Some(quota) = self.quota.filter(|_| !self.dry_run) {
+            for path in enforce_quota(&data_dir, &manifest, sizes, quota)? {
+                println!("Evicted {}", path.display());
+            }
+        }
+        match outcome {
+            ParamFetchOutcome::Complete => Ok(()),
+            ParamFetchOutcome::PartialSuccess { fetched, failed } => {
+                for (name, e) in &failed {
+                    eprintln!("{name}: {e:#}");
+                }
+                anyhow::bail!(
+                    "Fetched {} parameter files, failed to fetch {}",
+                    fetched.len(),
+                    failed.len()
+                )
+            }
+        }
+    }
+
+    async fn run_command(
+        &self,
+        command: &FetchParamsCommands,
+        manifest: &str,
+    ) -> anyhow::Result<()> {
+        match command {
+            FetchParamsCommands::Verify { concurrency } => {
+                let data_dir = self.data_dir()?;
+                let sizes = self.sector_sizes()?;
+                let fetch_config = self.fetch_config()?;
+                let report = match concurrency {
+                    Some(concurrency) => {
+                        let pb = indicatif::ProgressBar::new_spinner()
+                            .with_style(
+                                indicatif::ProgressStyle::with_template(
+                                    "{spinner} {bytes} hashed in {elapsed_precise}",
+                                )
+                                .expect("infallible"),
+                            )
+                            .with_finish(indicatif::ProgressFinish::AndClear);
+                        pb.enable_steady_tick(Duration::from_millis(100));
+                        verify_all(
+                            &data_dir,
+                            manifest,
+                            sizes,
+                            &fetch_config,
+                            (*concurrency).into(),
+                            Arc::new(move |hashed| pb.set_position(hashed)),
+                        )
+                        .await?
+                    }
+                    None => verify_params(&data_dir, manifest, sizes, &fetch_config).await?,
+                };
+                println!("{}", serde_json::to_string_pretty(&report)?);
+                anyhow::ensure!(report.is_ok(), "Parameter files verification failed");
+            }
+            FetchParamsCommands::VerifyParam { name } => {
+                let info = manifest_entry(manifest, name)?;
+                let manifest = serde_json::to_string(&BTreeMap::from([(name, info)]))?;
+                let report = verify_params(
+                    &self.data_dir()?,
+                    &manifest,
+                    SectorSizeOpt::All,
+                    &self.fetch_config()?,
+                )
+                .await?;
+                println!("{}", serde_json::to_string_pretty(&report)?);
+                anyhow::ensure!(report.is_ok(), "Parameter file verification failed");
+            }
+            FetchParamsCommands::VerifyStdin { name } => {
+                verify_stdin(name, manifest).await?;
+                println!("{name}: OK");
+            }
+            FetchParamsCommands::Cat { name } => {
+                let info = manifest_entry(manifest, name)?;
+                let reader = open_verified(&self.data_dir()?, name, &info).await?;
+                futures::io::copy(reader, &mut tokio::io::stdout().compat_write()).await?;
+            }
+            FetchParamsCommands::SupportedSizes => {
+                for size in supported_proofs(&self.data_dir()?, manifest).await? {
+                    println!("{}", size.as_bytes());
+                }
+            }
+            FetchParamsCommands::ListSizes => {
+                for size in manifest_sector_sizes(manifest)? {
+                    println!("{size}");
+                }
+            }
+            FetchParamsCommands::DiffManifest { old } => {
+                let old = std::fs::read_to_string(old)
+                    .with_context(|| format!("failed to read manifest {}", old.display()))?;
+                let diff = diff_manifests(&old, manifest)?;
+                println!("{}", serde_json::to_string_pretty(&diff)?);
+            }
+            FetchParamsCommands::BuildManifest { dir } => {
+                println!("{}", build_manifest(dir, manifest).await?);
+            }
+            FetchParamsCommands::PrintDigest => {
+                println!("{}", manifest_digest(manifest)?);
+            }
+            FetchParamsCommands::MigrateCache { renames } => {
+                let renames = std::fs::read_to_string(renames)
+                    .with_context(|| format!("failed to read renames {}", renames.display()))?;
+                let renames = serde_json::from_str(&renames).context("invalid renames")?;
+                for (old, new) in migrate_cache(&self.data_dir()?, manifest, &renames).await? {
+                    println!("Renamed {} to {}", old.display(), new.display());
+                }
+            }
+            FetchParamsCommands::PrintConfig => {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&self.fetch_config()?.resolved())?
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the data directory of the forest daemon configuration.
+    fn data_dir(&self) -> anyhow::Result<PathBuf> {
         let (_, config) = read_config(self.config.as_ref(), None)?;
+        Ok(config.client.data_dir)
+    }
 
-        let sizes = if self.all {
+    /// Returns the parameter files selected by the sector size options.
+    fn sector_sizes(&self) -> anyhow::Result<SectorSizeOpt> {
+        Ok(if self.all {
             SectorSizeOpt::All
         } else if let Some(size) = &self.params_size {
-            let sector_size = ram_to_int(size)?;
-            SectorSizeOpt::Size(sector_size)
+            SectorSizeOpt::Size(ram_to_int(size)?)
+        } else if let Some(size) = &self.keys_for_size {
+            SectorSizeOpt::KeysForSize(ram_to_int(size)?)
         } else if self.keys {
             SectorSizeOpt::Keys
         } else {
             cli_error_and_die(
-                "Sector size option must be chosen. Choose between --all, --keys, or <size>",
+                "Sector size option must be chosen. Choose between --all, --keys, --keys-for-size <size>, or <size>",
                 1,
             );
+        })
+    }
+
+    /// Returns the fetch configuration set by the options.
+    fn fetch_config(&self) -> anyhow::Result<ParamFetchConfig> {
+        let manifest_signature = match (&self.manifest_signature, &self.manifest_public_key) {
+            (Some(signature), Some(public_key)) => Some(ManifestSignature {
+                signature: hex::decode(signature).context("invalid manifest signature")?,
+                public_key: hex::decode(public_key).context("invalid manifest public key")?,
+            }),
+            _ => None,
+        };
+        let hash_pool = match self.hash_threads {
+            Some(threads) => Some(Arc::new(
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads.into())
+                    .thread_name(|i| format!("param-hash-{i}"))
+                    .build()?,
+            )),
+            None => None,
         };
+        Ok(ParamFetchConfig {
+            hash_pool,
+            trusted_sources: (!self.trusted_sources.is_empty())
+                .then(|| self.trusted_sources.clone()),
+            manifest_signature,
+            mismatch_policy: if self.quarantine_corrupt {
+                MismatchPolicy::Quarantine
+            } else {
+                MismatchPolicy::Delete
+            },
+            download_budget: self
+                .download_budget
+                .map(|limit| Arc::new(DownloadBudget::new(limit))),
+            download_chunks: self.download_chunks.into(),
+            base_dir: self.base_dir.clone(),
+            store: self
+                .store_dir
+                .as_ref()
+                .map(|dir| Arc::new(LocalParamStore::new(dir)) as Arc<dyn ParamStore>),
+            local_address: self.local_address,
+            write_lockfile: self.write_lockfile,
+            locked: self.locked,
+            best_effort: self.best_effort,
+            trust_above: self.trust_params_above,
+            include_optional: self.include_optional,
+            lenient_content_length: self.lenient_content_length,
+            warm_up: self.warm_up,
+            deadline: self
+                .deadline
+                .map(|secs| Instant::now() + Duration::from_secs(secs)),
+            credentials: self.credentials()?,
+            ..Default::default()
+        })
+    }
 
-        get_params_default(&config.client.data_dir, sizes, self.dry_run).await
+    /// Returns the credentials for the IPFS gateway, read from the given file or else from the
+    /// environment, so that they do not show in the process list.
+    fn credentials(&self) -> anyhow::Result<Option<Credentials>> {
+        let read = |path: &PathBuf| {
+            std::fs::read_to_string(path)
+                .map(|content| content.trim().to_owned())
+                .with_context(|| format!("failed to read credentials {}", path.display()))
+        };
+        if let Some(path) = &self.bearer_token_file {
+            return parse_bearer_token(&read(path)?).map(Some);
+        }
+        if let Some(path) = &self.basic_auth_file {
+            return parse_basic_auth(&read(path)?).map(Some);
+        }
+        if let Ok(token) = std::env::var(BEARER_TOKEN_ENV) {
+            return parse_bearer_token(&token).map(Some);
+        }
+        if let Ok(auth) = std::env::var(BASIC_AUTH_ENV) {
+            return parse_basic_auth(&auth).map(Some);
+        }
+        Ok(None)
     }
 }
 
+/// Environment variable the bearer token for the IPFS gateway is read from.
+const BEARER_TOKEN_ENV: &str = "FOREST_PARAMS_BEARER_TOKEN";
+/// Environment variable the `user[:password]` for the IPFS gateway is read from.
+const BASIC_AUTH_ENV: &str = "FOREST_PARAMS_BASIC_AUTH";
+
+fn parse_bearer_token(token: &str) -> anyhow::Result<Credentials> {
+    anyhow::ensure!(!token.is_empty(), "Bearer token must not be empty");
+    Ok(Credentials::Bearer(token.to_owned()))
+}
+
+fn parse_basic_auth(auth: &str) -> anyhow::Result<Credentials> {
+    let (username, password) = match auth.split_once(':') {
+        Some((username, password)) => (username, Some(password.to_owned())),
+        None => (auth, None),
+    };
+    anyhow::ensure!(
+        !username.is_empty(),
+        "Basic auth username must not be empty"
+    );
+    Ok(Credentials::Basic {
+        username: username.to_owned(),
+        password,
+    })
+}
+
 /// Converts a human readable string to a `u64` size.
 fn ram_to_int(size: &str) -> anyhow::Result<SectorSize> {
     // * there is no library to do this, but if other sector sizes are supported in
@@ -60,12 +480,15 @@ fn ram_to_int(size: &str) -> anyhow::Result<SectorSize> {
 
     type SectorSize = crate::shim::sector::SectorSize;
 
+    if let Some(size) = trimmed.parse().ok().and_then(SectorSize::from_bytes) {
+        return Ok(size);
+    }
     match trimmed {
-        "2048" | "2ki" => Ok(SectorSize::_2KiB),
-        "8388608" | "8mi" => Ok(SectorSize::_8MiB),
-        "536870912" | "512mi" => Ok(SectorSize::_512MiB),
-        "34359738368" | "32gi" => Ok(SectorSize::_32GiB),
-        "68719476736" | "64gi" => Ok(SectorSize::_64GiB),
+        "2ki" => Ok(SectorSize::_2KiB),
+        "8mi" => Ok(SectorSize::_8MiB),
+        "512mi" => Ok(SectorSize::_512MiB),
+        "32gi" => Ok(SectorSize::_32GiB),
+        "64gi" => Ok(SectorSize::_64GiB),
         _ => Err(anyhow::Error::msg(format!(
             "Failed to parse: {size}. Must be a valid sector size"
         ))),
@@ -88,5 +511,45 @@ mod tests {
         assert_eq!(ram_to_int("32Gi").unwrap(), SectorSize::_32GiB);
         assert_eq!(ram_to_int("32GiB").unwrap(), SectorSize::_32GiB);
         assert_eq!(ram_to_int("64Gib").unwrap(), SectorSize::_64GiB);
+        assert_eq!(ram_to_int("68719476736").unwrap(), SectorSize::_64GiB);
+        assert!(ram_to_int("4096").is_err());
+    }
+
+    #[test]
+    fn parse_subcommands() {
+        #[derive(clap::Parser)]
+        struct Cli {
+            #[command(flatten)]
+            fetch: FetchCommands,
+        }
+        let parse = |args: &[&str]| {
+            <Cli as clap::Parser>::try_parse_from(
+                std::iter::once("fetch-params").chain(args.iter().copied()),
+            )
+            .map(|cli| cli.fetch)
+        };
+
+        let fetch = parse(&["2048"]).unwrap();
+        assert_eq!(fetch.params_size.as_deref(), Some("2048"));
+        assert!(fetch.command.is_none());
+
+        let fetch = parse(&["--keys", "verify", "--concurrency", "4"]).unwrap();
+        assert!(fetch.keys);
+        assert!(matches!(
+            fetch.command,
+            Some(FetchParamsCommands::Verify {
+                concurrency: Some(4)
+            })
+        ));
+
+        let fetch = parse(&["--manifest", "manifest.json", "verify-param", "v28-a.vk"]).unwrap();
+        assert!(fetch.manifest.is_some());
+        assert!(matches!(
+            fetch.command,
+            Some(FetchParamsCommands::VerifyParam { name }) if name == "v28-a.vk"
+        ));
+
+        // The early-exit flags are gone.
+        parse(&["--print-digest"]).unwrap_err();
     }
 }
@@ -11,6 +11,7 @@ use crate::{
         types::ApiTipsetKey,
         ApiPath, RpcMethodExt as _,
     },
+    shim::{clock::ChainEpoch, sector::SealVerifyInfo},
 };
 use anyhow::Context as _;
 use base64::{prelude::BASE64_STANDARD, Engine};
@@ -50,6 +51,61 @@ pub enum ShedCommands {
         #[arg(short, long)]
         output: Option<PathBuf>,
     },
+    /// Print the `drand` chain infos bundled with Forest, one per line, in the JSON format of the
+    /// `info` endpoint of `drand`.
+    DrandInfo,
+    /// Verify the signature of a `drand` beacon entry against the bundled chain info, without
+    /// querying any `drand` server.
+    VerifyBeacon {
+        /// Hash of the `drand` beacon chain, as printed by `drand-info`.
+        #[arg(long)]
+        chain: String,
+        /// Round of the beacon entry.
+        #[arg(long)]
+        round: u64,
+        /// Hex-encoded signature of the beacon entry.
+        #[arg(long)]
+        signature: String,
+        /// Hex-encoded signature of the previous round, for chained beacons.
+        #[arg(long, default_value = "")]
+        previous_signature: String,
+    },
+    /// Print the round of a `drand` beacon chain produced at the given time, from the bundled
+    /// chain info.
+    DrandRound {
+        /// Hash of the `drand` beacon chain, as printed by `drand-info`.
+        #[arg(long)]
+        chain: String,
+        /// UNIX timestamp, in seconds, e.g., from a trusted time source. Defaults to the system
+        /// clock.
+        #[arg(long)]
+        at: Option<u64>,
+    },
+    /// Print the rounds of a `drand` beacon chain covering a range of Filecoin epochs, one per
+    /// line, from the bundled chain info.
+    DrandRounds {
+        /// Hash of the `drand` beacon chain, as printed by `drand-info`.
+        #[arg(long)]
+        chain: String,
+        /// UNIX timestamp, in seconds, of the Filecoin genesis block.
+        #[arg(long)]
+        genesis_time: u64,
+        /// Duration of a Filecoin epoch, in seconds.
+        #[arg(long, default_value_t = 30)]
+        block_delay: u64,
+        /// First epoch of the range.
+        #[arg(long, value_parser = clap::value_parser!(ChainEpoch).range(0..))]
+        from: ChainEpoch,
+        /// Last epoch of the range, inclusive.
+        #[arg(long, value_parser = clap::value_parser!(ChainEpoch).range(0..))]
+        to: ChainEpoch,
+    },
+    /// Check that the proof of a seal verify info has the length expected for its registered
+    /// proof type, without verifying the proof itself.
+    ValidateSealVerifyInfo {
+        /// Hex-encoded CBOR of the seal verify info.
+        info: String,
+    },
     /// Dump the OpenRPC definition for the node.
     Openrpc {
         include: Vec<String>,
@@ -121,6 +177,67 @@ impl ShedCommands {
                     println!("{}", BASE64_STANDARD.encode(keypair_data));
                 }
             }
+            ShedCommands::DrandInfo => {
+                for config in crate::networks::all_drand_configs() {
+                    println!("{}", config.chain_info.to_group_json());
+                }
+            }
+            ShedCommands::VerifyBeacon {
+                chain,
+                round,
+                signature,
+                previous_signature,
+            } => {
+                let config = crate::networks::all_drand_configs()
+                    .iter()
+                    .find(|config| config.chain_info.hash == chain)
+                    .with_context(|| format!("unknown drand chain {chain}"))?;
+                let valid = config.chain_info.verify_beacon(
+                    round,
+                    &hex::decode(previous_signature).context("invalid previous signature")?,
+                    &hex::decode(signature).context("invalid signature")?,
+                )?;
+                anyhow::ensure!(valid, "invalid signature for round {round}");
+                println!("Valid signature for round {round}");
+            }
+            ShedCommands::DrandRound { chain, at } => {
+                let config = crate::networks::all_drand_configs()
+                    .iter()
+                    .find(|config| config.chain_info.hash == chain)
+                    .with_context(|| format!("unknown drand chain {chain}"))?;
+                let now = match at {
+                    Some(secs) => std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs),
+                    None => std::time::SystemTime::now(),
+                };
+                println!("{}", config.chain_info.current_round(now, None));
+            }
+            ShedCommands::DrandRounds {
+                chain,
+                genesis_time,
+                block_delay,
+                from,
+                to,
+            } => {
+                let config = crate::networks::all_drand_configs()
+                    .iter()
+                    .find(|config| config.chain_info.hash == chain)
+                    .with_context(|| format!("unknown drand chain {chain}"))?;
+                // The round of an epoch is the one produced at the start of the previous epoch,
+                // see `DrandBeacon::max_beacon_round_for_epoch`.
+                let rounds = config.chain_info.rounds_for_epochs(from, to, |epoch| {
+                    (genesis_time + epoch as u64 * block_delay).saturating_sub(block_delay)
+                });
+                for round in rounds {
+                    println!("{round}");
+                }
+            }
+            ShedCommands::ValidateSealVerifyInfo { info } => {
+                let info: SealVerifyInfo =
+                    fvm_ipld_encoding::from_slice(&hex::decode(info).context("invalid hex")?)
+                        .context("invalid seal verify info")?;
+                info.validate_proof_len()?;
+                println!("Valid proof length for {:?}", info.registered_proof);
+            }
             ShedCommands::Openrpc { include, path } => {
                 let include = include.iter().map(String::as_str).collect::<Vec<_>>();
                 println!(
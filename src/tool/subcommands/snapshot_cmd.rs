@@ -417,7 +417,7 @@ where
         &Config::default().client.data_dir,
     );
 
-    ensure_params_downloaded().await?;
+    ensure_params_downloaded(&network).await?;
 
     let chain_index = Arc::new(ChainIndex::new(Arc::new(db.clone())));
 
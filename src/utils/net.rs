@@ -3,16 +3,12 @@
 
 use crate::utils::io::WithProgress;
 use crate::utils::reqwest_resume;
-use cid::Cid;
-use futures::{AsyncWriteExt, TryStreamExt};
+use futures::TryStreamExt;
 use reqwest::Response;
-use std::path::Path;
+use std::time::Duration;
 use tap::Pipe;
 use tokio::io::AsyncBufRead;
-use tokio_util::{
-    compat::TokioAsyncReadCompatExt,
-    either::Either::{Left, Right},
-};
+use tokio_util::either::Either::{Left, Right};
 use tracing::info;
 use url::Url;
 
@@ -23,34 +19,53 @@ pub fn global_http_client() -> reqwest::Client {
     CLIENT.clone()
 }
 
-/// Download a file via IPFS HTTP gateway in trustless mode.
-/// See <https://github.com/ipfs/specs/blob/main/http-gateways/TRUSTLESS_GATEWAY.md>
-pub async fn download_ipfs_file_trustlessly(
-    cid: &Cid,
-    gateway: &Url,
-    destination: &Path,
-) -> anyhow::Result<()> {
-    let url = {
-        let mut url = gateway.join(&cid.to_string())?;
-        url.set_query(Some("format=car"));
-        Ok::<_, anyhow::Error>(url)
-    }?;
+/// Exponential backoff settings shared by the network retry sites, so that they are configured
+/// consistently. Converts into [`backoff::ExponentialBackoff`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BackoffConfig {
+    /// Give up retrying after this much time, or never if `None`.
+    pub max_elapsed_time: Option<Duration>,
+    /// Delay before the first retry.
+    pub initial_interval: Duration,
+    /// Factor the delay is multiplied by after each retry.
+    pub multiplier: f64,
+    /// Jitter applied to each delay, as a fraction of it.
+    pub randomization_factor: f64,
+}
 
-    let tmp =
-        tempfile::NamedTempFile::new_in(destination.parent().unwrap_or_else(|| Path::new(".")))?
-            .into_temp_path();
-    {
-        let mut reader = reader(url.as_str()).await?.compat();
-        let mut writer = futures::io::BufWriter::new(async_fs::File::create(&tmp).await?);
-        rs_car_ipfs::single_file::read_single_file_seek(&mut reader, &mut writer, Some(cid))
-            .await?;
-        writer.flush().await?;
-        writer.close().await?;
+impl Default for BackoffConfig {
+    fn default() -> Self {
+        Self {
+            max_elapsed_time: Some(Duration::from_millis(
+                backoff::default::MAX_ELAPSED_TIME_MILLIS,
+            )),
+            initial_interval: Duration::from_millis(backoff::default::INITIAL_INTERVAL_MILLIS),
+            multiplier: backoff::default::MULTIPLIER,
+            randomization_factor: backoff::default::RANDOMIZATION_FACTOR,
+        }
     }
+}
 
-    tmp.persist(destination)?;
+impl From<BackoffConfig> for backoff::ExponentialBackoff {
+    fn from(config: BackoffConfig) -> Self {
+        backoff::ExponentialBackoffBuilder::default()
+            .with_max_elapsed_time(config.max_elapsed_time)
+            .with_initial_interval(config.initial_interval)
+            .with_multiplier(config.multiplier)
+            .with_randomization_factor(config.randomization_factor)
+            .build()
+    }
+}
 
-    Ok(())
+impl From<&backoff::ExponentialBackoff> for BackoffConfig {
+    fn from(backoff: &backoff::ExponentialBackoff) -> Self {
+        Self {
+            max_elapsed_time: backoff.max_elapsed_time,
+            initial_interval: backoff.initial_interval,
+            multiplier: backoff.multiplier,
+            randomization_factor: backoff.randomization_factor,
+        }
+    }
 }
 
 /// `location` may be:
@@ -98,3 +113,25 @@ pub async fn http_get(url: &Url) -> anyhow::Result<Response> {
         .await?
         .error_for_status()?)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_config_round_trip() {
+        assert_eq!(
+            BackoffConfig::from(&backoff::ExponentialBackoff::default()),
+            BackoffConfig::default()
+        );
+
+        let config = BackoffConfig {
+            max_elapsed_time: None,
+            initial_interval: Duration::from_secs(2),
+            multiplier: 3.0,
+            randomization_factor: 0.1,
+        };
+        let backoff: backoff::ExponentialBackoff = config.into();
+        assert_eq!(BackoffConfig::from(&backoff), config);
+    }
+}
@@ -0,0 +1,79 @@
+// Copyright 2019-2024 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::path::PathBuf;
+
+use cid::Cid;
+use thiserror::Error;
+use url::Url;
+
+/// Errors specific to fetching and verifying the proofs parameters. They are wrapped in
+/// [`anyhow::Error`] and can be recovered with [`anyhow::Error::downcast_ref`].
+#[derive(Debug, Error)]
+pub enum ParamFetchError {
+    /// The manifest declares a schema version this build does not understand.
+    #[error("Unsupported parameter manifest version: {0}")]
+    UnsupportedManifestVersion(u64),
+    /// The download was redirected more times than allowed.
+    #[error("Too many redirects while fetching {0}")]
+    TooManyRedirects(Url),
+    /// The manifest does not match its detached signature.
+    #[error("Invalid parameter manifest signature")]
+    InvalidManifestSignature,
+    /// The fetched manifest does not match the expected hash.
+    #[error("Parameter manifest hash mismatch: expected {expected}, got {actual}")]
+    ManifestHashMismatch { expected: String, actual: String },
+    /// The parameter file does not match the digest in the manifest.
+    #[error("Checksum mismatch in param file {}. ({actual} != {expected})", path.display())]
+    ChecksumMismatch {
+        path: PathBuf,
+        actual: String,
+        expected: String,
+    },
+    /// The parameter file does not have the size declared in the manifest.
+    #[error("Size mismatch in param file {}. ({actual} != {expected})", path.display())]
+    SizeMismatch {
+        path: PathBuf,
+        actual: u64,
+        expected: u64,
+    },
+    /// Some chunks of the parameter file do not match their digests in the manifest, see
+    /// [`super::parameters::ParamChunks`]. They can be fetched again individually.
+    #[error("Checksum mismatch in chunks {chunks:?} of param file {}", path.display())]
+    CorruptChunks { path: PathBuf, chunks: Vec<usize> },
+    /// The parameter file is empty, e.g., due to an interrupted download.
+    #[error("Parameter file {} is empty", .0.display())]
+    EmptyFile(PathBuf),
+    /// There is not enough disk space for the parameter file, detected before downloading it.
+    #[error("Not enough disk space for {} ({} needed)", path.display(), human_bytes::human_bytes(*needed as f64))]
+    DiskFull { path: PathBuf, needed: u64 },
+    /// The HTTP client used for downloading the parameter files could not be created.
+    #[error("Failed to create the HTTP client with the {tls_backend} TLS backend")]
+    ClientInit {
+        tls_backend: &'static str,
+        #[source]
+        source: reqwest::Error,
+    },
+    /// The parameter file is not locked to the CID in the manifest, see
+    /// [`super::ParamFetchConfig::locked`].
+    #[error("Parameter file {name} CID {manifest} does not match the lockfile ({})", locked.as_ref().map_or_else(|| "not locked".to_owned(), |cid| cid.to_string()))]
+    LockfileMismatch {
+        name: String,
+        // Boxed to keep the error small.
+        locked: Option<Box<Cid>>,
+        manifest: Box<Cid>,
+    },
+    /// The deadline of the fetch passed before the parameter file was fetched, see
+    /// [`super::ParamFetchConfig::deadline`].
+    #[error("Deadline exceeded before the parameter file was fetched")]
+    DeadlineExceeded,
+    /// The parameter cache directory is a symbolic link to a missing target.
+    #[error("Parameter cache directory {} is a dangling symlink to {}", path.display(), target.display())]
+    DanglingCacheDir { path: PathBuf, target: PathBuf },
+    /// The parameter file exists but cannot be read. Downloading it again would not help.
+    #[error("Permission denied reading param file {}, make it readable by the user running Forest", path.display())]
+    PermissionDenied { path: PathBuf },
+    /// The download budget was exhausted before all the parameter files were fetched.
+    #[error("Download budget exceeded, remaining parameter files: {}", remaining.join(", "))]
+    BudgetExceeded { remaining: Vec<String> },
+}
@@ -0,0 +1,131 @@
+// Copyright 2019-2024 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! Lockfile recording the parameter files fetched by [`super::get_params`], so that the same
+//! files can be fetched again in another environment. Under
+//! [`super::ParamFetchConfig::locked`], files whose CID differs from the lockfile are refused.
+
+use std::{collections::BTreeMap, path::Path};
+
+use cid::Cid;
+use serde::{Deserialize, Serialize};
+
+use super::errors::ParamFetchError;
+use super::parameters::{ParamDigests, ParameterData};
+
+/// Name of the lockfile in the parameter cache directory.
+pub(super) const LOCKFILE_NAME: &str = "params.lock";
+
+/// Locked state of a single parameter file.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub(super) struct LockedParam {
+    #[serde(with = "crate::lotus_json::stringify")]
+    pub cid: Cid,
+    /// Digests the file was verified against when fetched.
+    pub digest: ParamDigests,
+}
+
+/// Contents of the lockfile, keyed by parameter file name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub(super) struct ParamsLock(BTreeMap<String, LockedParam>);
+
+impl ParamsLock {
+    /// Reads the lockfile from the given directory. A missing lockfile is empty.
+    pub fn read(dir: &Path) -> anyhow::Result<Self> {
+        match std::fs::read_to_string(dir.join(LOCKFILE_NAME)) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Writes the lockfile to the given directory.
+    pub fn write(&self, dir: &Path) -> anyhow::Result<()> {
+        std::fs::write(dir.join(LOCKFILE_NAME), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Records the verified parameter files.
+    pub fn extend<'a>(
+        &mut self,
+        params: impl IntoIterator<Item = (&'a String, &'a ParameterData)>,
+    ) {
+        self.0.extend(params.into_iter().map(|(name, info)| {
+            (
+                name.clone(),
+                LockedParam {
+                    cid: info.cid,
+                    digest: info.digest.clone(),
+                },
+            )
+        }));
+    }
+
+    /// Fails with [`ParamFetchError::LockfileMismatch`] if any of the parameter files is not
+    /// locked to the same CID.
+    pub fn check<'a>(
+        &self,
+        params: impl IntoIterator<Item = (&'a String, &'a ParameterData)>,
+    ) -> Result<(), ParamFetchError> {
+        for (name, info) in params {
+            let locked = self.0.get(name).map(|locked| locked.cid);
+            if locked != Some(info.cid) {
+                return Err(ParamFetchError::LockfileMismatch {
+                    name: name.clone(),
+                    locked: locked.map(Box::new),
+                    manifest: Box::new(info.cid),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::proofs_api::parameters::DEFAULT_PARAMETERS;
+    use crate::utils::proofs_api::paramfetch::{manifest_entries, SectorSizeOpt};
+
+    #[test]
+    fn test_lockfile_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(ParamsLock::read(dir.path()).unwrap(), ParamsLock::default());
+
+        let entries = manifest_entries(DEFAULT_PARAMETERS, SectorSizeOpt::Keys).unwrap();
+        let mut lock = ParamsLock::default();
+        lock.extend(entries.iter().map(|(name, info)| (name, info)));
+        lock.write(dir.path()).unwrap();
+
+        let read = ParamsLock::read(dir.path()).unwrap();
+        assert_eq!(read, lock);
+        read.check(entries.iter().map(|(name, info)| (name, info)))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_lockfile_cid_change_rejected() {
+        let entries = manifest_entries(DEFAULT_PARAMETERS, SectorSizeOpt::Keys).unwrap();
+        let mut lock = ParamsLock::default();
+        lock.extend(entries.iter().map(|(name, info)| (name, info)));
+
+        let (name, info) = entries.first().unwrap();
+        let changed = ParameterData {
+            cid: Cid::default(),
+            ..info.clone()
+        };
+        let err = lock.check([(name, &changed)]).unwrap_err();
+        assert!(matches!(
+            err,
+            ParamFetchError::LockfileMismatch { name: n, locked: Some(locked), manifest }
+                if n == *name && *locked == info.cid && *manifest == Cid::default()
+        ));
+
+        // Files missing from the lockfile are refused as well.
+        let err = ParamsLock::default().check([(name, info)]).unwrap_err();
+        assert!(matches!(
+            err,
+            ParamFetchError::LockfileMismatch { locked: None, .. }
+        ));
+    }
+}
@@ -1,8 +1,27 @@
 // Copyright 2019-2024 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+mod errors;
+mod lockfile;
 mod parameters;
 mod paramfetch;
+mod store;
+#[cfg(test)]
+pub mod test_params;
+mod verify;
 
-pub use parameters::set_proofs_parameter_cache_dir_env;
-pub use paramfetch::{ensure_params_downloaded, get_params_default, SectorSizeOpt};
+pub use parameters::{
+    build_manifest, diff_manifests, expected_params_version, manifest_digest, manifest_entry,
+    manifest_sector_sizes, set_proofs_parameter_cache_dir_env, ManifestSignature,
+    DEFAULT_PARAMETERS,
+};
+pub use paramfetch::{
+    enforce_quota, ensure_params_downloaded, fetch_manifest, get_params, migrate_cache,
+    missing_params, params_cache_dir, Credentials, DownloadBudget, MismatchPolicy,
+    ParamFetchConfig, ParamFetchOutcome, ParamProvenance, ParamProvenanceLog, SectorSizeOpt,
+};
+pub use store::{LocalParamStore, ParamStore};
+pub use verify::{
+    open_verified, spawn_param_revalidator, supported_proofs, verify_all, verify_params,
+    verify_stdin,
+};
@@ -6,24 +6,43 @@
 //! using the [`PROOFS_PARAMETER_CACHE_ENV`] environment variable. If not set, the default directory is used.
 
 use std::{
+    collections::{BTreeMap, BTreeSet},
     fs::File as SyncFile,
-    io::{self, copy as sync_copy, BufReader as SyncBufReader},
+    io::{self, Read as _},
     path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
 };
 
 use ahash::HashMap;
-use anyhow::{bail, Context};
-use blake2b_simd::{Hash, State as Blake2b};
+use anyhow::Context;
+use blake2b_simd::State as Blake2b;
+use bls_signatures::{PublicKey as BlsPublicKey, Serialize as _, Signature as BlsSignature};
 use cid::Cid;
+use futures::{future::BoxFuture, AsyncReadExt as _, FutureExt as _};
+use once_cell::sync::Lazy;
+use rayon::ThreadPool;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
 use tracing::{debug, warn};
 
-use crate::utils::misc::env::is_env_truthy;
+use super::errors::ParamFetchError;
+use super::store::ParamStore;
+use crate::utils::{encoding::blake2b_256, misc::env::is_env_truthy};
 
 const PROOF_DIGEST_LEN: usize = 16;
 
+/// Length of the full-length BLAKE2b-256 and SHA-256 digests of the parameter files.
+const FULL_DIGEST_LEN: usize = 32;
+
+/// Size of the chunks the parameter files are hashed in. The hashing progress is reported after
+/// each of them.
+const HASH_CHUNK_SIZE: usize = 1024 * 1024;
+
 /// Environment variable that allows skipping checksum verification of the parameter files.
-const FOREST_FORCE_TRUST_PARAMS_ENV: &str = "FOREST_FORCE_TRUST_PARAMS";
+pub(super) const FOREST_FORCE_TRUST_PARAMS_ENV: &str = "FOREST_FORCE_TRUST_PARAMS";
 
 /// Environment variable to set the directory where proofs parameters are stored. Defaults to
 /// [`PARAM_DIR`] in the data directory.
@@ -32,66 +51,1011 @@ pub(super) const PROOFS_PARAMETER_CACHE_ENV: &str = "FIL_PROOFS_PARAMETER_CACHE"
 /// Default directory name for storing proofs parameters.
 const PARAM_DIR: &str = "filecoin-proof-parameters";
 
+/// Environment variable to set the name of the directory in the data directory where proofs
+/// parameters are stored, instead of [`PARAM_DIR`]. Ignored if [`PROOFS_PARAMETER_CACHE_ENV`] is
+/// set, which overrides the whole path.
+pub(super) const PARAM_DIR_NAME_ENV: &str = "FOREST_PROOFS_PARAM_DIR_NAME";
+
 /// Default parameters, as outlined in Lotus `v1.26.2`.
 /// <https://github.com/filecoin-project/filecoin-ffi/blob/b715c9403faf919e95fdc702cd651e842f18d890/parameters.json>
-pub(super) const DEFAULT_PARAMETERS: &str = include_str!("./parameters.json");
+pub const DEFAULT_PARAMETERS: &str = include_str!("./parameters.json");
 
 /// Map of parameter data, to be deserialized from the parameter file.
 pub(super) type ParameterMap = HashMap<String, ParameterData>;
 
+/// Optional top-level manifest key holding the schema version.
+const MANIFEST_VERSION_KEY: &str = "version";
+
+/// Manifest schema versions understood by this build. Manifests without a version are assumed
+/// to be compatible.
+const SUPPORTED_MANIFEST_VERSIONS: &[u64] = &[1];
+
 /// Data structure for retrieving the proof parameter data from provided JSON.
 #[derive(Debug, Deserialize, Serialize, Clone)]
-pub(super) struct ParameterData {
+pub struct ParameterData {
+    /// CID of the parameter file, used for fetching it from IPFS.
     #[serde(with = "crate::lotus_json::stringify")]
     pub cid: Cid,
-    #[serde(with = "hex::serde")]
-    pub digest: [u8; PROOF_DIGEST_LEN],
+    /// Truncated BLAKE2b digest of the parameter file.
+    pub digest: ParamDigests,
+    /// Sector size the parameter file is used for, or `0` if not bound to one.
     pub sector_size: u64,
+    /// Expected size of the parameter file in bytes, if known. Checked before the digest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Whether the parameter file is only needed for proof types some nodes never use. Optional
+    /// files are skipped unless [`super::ParamFetchConfig::include_optional`] is set.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+    /// Optional digests of the chunks of the parameter file, so that the corrupt chunks can be
+    /// fetched again instead of the whole file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunks: Option<ParamChunks>,
+    /// Optional SHA-256 digest of the parameter file, verified along with
+    /// [`ParameterData::digest`] for defense in depth. A file matching only one of them is
+    /// rejected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<Sha256Digest>,
+}
+
+/// Hex-encoded SHA-256 digest of a parameter file, see [`ParameterData::sha256`].
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Sha256Digest(#[serde(with = "hex::serde")] pub [u8; FULL_DIGEST_LEN]);
+
+/// Digests of the consecutive fixed-size chunks of a parameter file.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct ParamChunks {
+    /// Size of the chunks in bytes. The last chunk may be shorter.
+    pub size: u64,
+    /// Digest of each chunk, in order. Full-length chunk digests are BLAKE2b-256.
+    pub digests: Vec<ParamDigest>,
+}
+
+/// Type of a parameter file, derived from the extension of its name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamFileKind {
+    /// Groth16 parameters used for generating proofs, `.params`.
+    Params,
+    /// Groth16 verifying key, `.vk`.
+    VerifyingKey,
+    /// Structured reference string used for verifying aggregated proofs, `.srs`.
+    Srs,
+}
+
+impl ParamFileKind {
+    /// Classifies a parameter file by its name, or returns `None` for unknown extensions.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match Path::new(name).extension()?.to_str()? {
+            "params" => Some(Self::Params),
+            "vk" => Some(Self::VerifyingKey),
+            "srs" => Some(Self::Srs),
+            _ => None,
+        }
+    }
+
+    /// Returns whether files of this kind are needed for verifying proofs.
+    pub fn is_verification(self) -> bool {
+        matches!(self, Self::VerifyingKey | Self::Srs)
+    }
+}
+
+/// Parses a parameter manifest, rejecting schema versions this build does not understand with
+/// [`ParamFetchError::UnsupportedManifestVersion`].
+pub(super) fn parse_manifest(param_json: &str) -> anyhow::Result<ParameterMap> {
+    let mut manifest: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(param_json)?;
+    if let Some(version) = manifest.remove(MANIFEST_VERSION_KEY) {
+        let version = version
+            .as_u64()
+            .context("manifest version must be an unsigned integer")?;
+        if !SUPPORTED_MANIFEST_VERSIONS.contains(&version) {
+            return Err(ParamFetchError::UnsupportedManifestVersion(version).into());
+        }
+    }
+    Ok(serde_json::from_value(manifest.into())?)
+}
+
+/// Returns the entry of the parameter file `name` in the given manifest.
+pub fn manifest_entry(param_json: &str, name: &str) -> anyhow::Result<ParameterData> {
+    parse_manifest(param_json)?
+        .remove(name)
+        .with_context(|| format!("Parameter file {name} is not in the manifest"))
+}
+
+/// Hex-encoded digest of a parameter file. The hash algorithm is told apart by the length of
+/// the digest, so that stronger digests are supported without a schema change.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ParamDigest {
+    /// BLAKE2b-256 digest truncated to [`PROOF_DIGEST_LEN`] bytes, see [`blake2b_digest`].
+    Truncated(#[serde(with = "hex::serde")] [u8; PROOF_DIGEST_LEN]),
+    /// BLAKE2b-256 or SHA-256 digest.
+    Full(#[serde(with = "hex::serde")] [u8; FULL_DIGEST_LEN]),
+}
+
+impl ParamDigest {
+    fn to_hex(self) -> String {
+        match self {
+            Self::Truncated(digest) => hex::encode(digest),
+            Self::Full(digest) => hex::encode(digest),
+        }
+    }
+}
+
+/// Acceptable digests of a parameter file. During a parameter transition, the file may
+/// legitimately match either the old or the new digest. A single digest is kept as a plain
+/// string for backward compatibility.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ParamDigests {
+    /// The only acceptable digest.
+    Single(ParamDigest),
+    /// Any of the listed digests is acceptable.
+    Multiple(Vec<ParamDigest>),
+}
+
+impl ParamDigests {
+    /// Returns the acceptable digests, hex-encoded.
+    fn to_hex(&self) -> Vec<String> {
+        match self {
+            Self::Single(digest) => vec![digest.to_hex()],
+            Self::Multiple(digests) => digests.iter().map(|d| d.to_hex()).collect(),
+        }
+    }
+}
+
+impl From<[u8; PROOF_DIGEST_LEN]> for ParamDigests {
+    fn from(digest: [u8; PROOF_DIGEST_LEN]) -> Self {
+        Self::Single(ParamDigest::Truncated(digest))
+    }
+}
+
+/// Detached BLS signature over the raw bytes of a parameter manifest, along with the public key
+/// of the signer.
+#[derive(Debug, Clone)]
+pub struct ManifestSignature {
+    /// Signature over the manifest bytes.
+    pub signature: Vec<u8>,
+    /// Public key of the signer.
+    pub public_key: Vec<u8>,
+}
+
+/// Verifies the manifest against its detached signature, guarding against a compromised manifest
+/// swapping in different parameter files. Fails with [`ParamFetchError::InvalidManifestSignature`]
+/// if the manifest was tampered with.
+pub(super) fn verify_manifest_signature(
+    param_json: &str,
+    signature: &ManifestSignature,
+) -> anyhow::Result<()> {
+    let public_key = BlsPublicKey::from_bytes(&signature.public_key)
+        .context("invalid manifest signer public key")?;
+    let sig = BlsSignature::from_bytes(&signature.signature)
+        .context("invalid manifest signature encoding")?;
+    if public_key.verify(sig, param_json) {
+        Ok(())
+    } else {
+        Err(ParamFetchError::InvalidManifestSignature.into())
+    }
+}
+
+/// Returns the distinct sector sizes referenced by the given parameter manifest. Parameters
+/// not bound to a sector size, e.g., the inner product SRS, are reported as `0`.
+pub fn manifest_sector_sizes(param_json: &str) -> anyhow::Result<BTreeSet<u64>> {
+    Ok(parse_manifest(param_json)?
+        .into_values()
+        .map(|data| data.sector_size)
+        .collect())
+}
+
+/// Returns the hex-encoded BLAKE2b-256 digest of the parsed manifest, serialized with sorted
+/// keys. Unlike a hash of the raw bytes, it identifies the parameter set regardless of the
+/// formatting and key order of the manifest.
+pub fn manifest_digest(param_json: &str) -> anyhow::Result<String> {
+    let canonical: BTreeMap<_, _> = parse_manifest(param_json)?.into_iter().collect();
+    Ok(hex::encode(blake2b_256(&serde_json::to_vec(&canonical)?)))
+}
+
+/// Returns the version of the parameters the node expects, i.e., the [`manifest_digest`] of
+/// [`DEFAULT_PARAMETERS`], so that, e.g., a fleet manager can check that all its nodes expect the
+/// same parameters.
+pub fn expected_params_version() -> &'static str {
+    static VERSION: Lazy<String> = Lazy::new(|| {
+        manifest_digest(DEFAULT_PARAMETERS).expect("the bundled parameter manifest must be valid")
+    });
+    &VERSION
+}
+
+/// Changes between two parameter manifests, see [`diff_manifests`]. The names are sorted.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct ManifestDiff {
+    /// Parameter files only in the new manifest.
+    pub added: Vec<String>,
+    /// Parameter files only in the old manifest.
+    pub removed: Vec<String>,
+    /// Parameter files in both manifests with different entries.
+    pub changed: Vec<ManifestChange>,
+}
+
+/// Parameter file whose manifest entry changed, see [`ManifestDiff`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ManifestChange {
+    pub name: String,
+    /// Names of the changed fields of the entry, e.g., `cid` or `digest`.
+    pub fields: Vec<&'static str>,
+}
+
+/// Compares two parameter manifests, e.g., for reviewing an upgrade of [`DEFAULT_PARAMETERS`].
+pub fn diff_manifests(old_json: &str, new_json: &str) -> anyhow::Result<ManifestDiff> {
+    let old: BTreeMap<_, _> = parse_manifest(old_json)?.into_iter().collect();
+    let new: BTreeMap<_, _> = parse_manifest(new_json)?.into_iter().collect();
+    let mut diff = ManifestDiff {
+        removed: old
+            .keys()
+            .filter(|name| !new.contains_key(*name))
+            .cloned()
+            .collect(),
+        ..Default::default()
+    };
+    for (name, new_data) in new {
+        let Some(old_data) = old.get(&name) else {
+            diff.added.push(name);
+            continue;
+        };
+        let fields: Vec<_> = [
+            ("cid", old_data.cid != new_data.cid),
+            ("digest", old_data.digest != new_data.digest),
+            ("sector_size", old_data.sector_size != new_data.sector_size),
+            ("size", old_data.size != new_data.size),
+            ("optional", old_data.optional != new_data.optional),
+            ("chunks", old_data.chunks != new_data.chunks),
+            ("sha256", old_data.sha256 != new_data.sha256),
+        ]
+        .into_iter()
+        .filter_map(|(field, changed)| changed.then_some(field))
+        .collect();
+        if !fields.is_empty() {
+            diff.changed.push(ManifestChange { name, fields });
+        }
+    }
+    Ok(diff)
+}
+
+/// Callback invoked with the number of bytes of a parameter file hashed so far.
+pub type HashProgressFn = Arc<dyn Fn(u64) + Send + Sync>;
+
+/// Builds a manifest, compatible with [`DEFAULT_PARAMETERS`], of the files in the given directory,
+/// e.g., for setting up an offline mirror. The digests are computed from the files, while the
+/// CIDs and sector sizes are taken from the entries of `known` with the same name, if any, and
+/// left blank otherwise. Only the parameter files, see [`ParamFileKind`], are included, not the
+/// lock, marker, index or quarantined files living alongside them.
+pub async fn build_manifest(dir: &Path, known: &str) -> anyhow::Result<String> {
+    let known = parse_manifest(known)?;
+    let mut manifest = BTreeMap::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let Some(name) = entry
+            .file_name()
+            .to_str()
+            .filter(|name| ParamFileKind::from_name(name).is_some())
+            .map(str::to_owned)
+        else {
+            continue;
+        };
+        let size = entry.metadata()?.len();
+        let digest = blake2b_digest(&entry.path(), None).await?;
+        let digest: [u8; PROOF_DIGEST_LEN] = hex::decode(digest)?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("invalid digest length"))?;
+        let (cid, sector_size, optional) =
+            known.get(&name).map_or((Cid::default(), 0, false), |info| {
+                (info.cid, info.sector_size, info.optional)
+            });
+        manifest.insert(
+            name,
+            ParameterData {
+                cid,
+                digest: digest.into(),
+                sector_size,
+                size: Some(size),
+                optional,
+                chunks: None,
+                sha256: None,
+            },
+        );
+    }
+    Ok(serde_json::to_string_pretty(&manifest)?)
+}
+
+/// Asynchronous function computing the hex-encoded digest of the file at the given path,
+/// optionally reporting the hashing progress. It is used for verifying the parameter files, see
+/// [`blake2b_digest`] for the default.
+pub type DigestFn = Arc<
+    dyn Fn(&Path, Option<HashProgressFn>) -> BoxFuture<'static, anyhow::Result<String>>
+        + Send
+        + Sync,
+>;
+
+/// Sets the flag once dropped, i.e., when the future owning it is cancelled or completes.
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Hash algorithm of a [`ParamDigest`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DigestAlgo {
+    /// BLAKE2b-256 truncated to [`PROOF_DIGEST_LEN`] bytes.
+    TruncatedBlake2b,
+    Blake2b256,
+    Sha256,
+}
+
+impl DigestAlgo {
+    /// Length of the hex-encoded digest.
+    fn hex_len(self) -> usize {
+        match self {
+            Self::TruncatedBlake2b => PROOF_DIGEST_LEN * 2,
+            Self::Blake2b256 | Self::Sha256 => FULL_DIGEST_LEN * 2,
+        }
+    }
+}
+
+/// Incremental state of a [`DigestAlgo`].
+enum FileHasher {
+    Blake2b(Blake2b, usize),
+    Sha256(Sha256),
+}
+
+impl FileHasher {
+    fn new(algo: DigestAlgo) -> Self {
+        match algo {
+            DigestAlgo::TruncatedBlake2b => Self::Blake2b(Blake2b::new(), PROOF_DIGEST_LEN),
+            DigestAlgo::Blake2b256 => Self::Blake2b(
+                blake2b_simd::Params::new()
+                    .hash_length(FULL_DIGEST_LEN)
+                    .to_state(),
+                FULL_DIGEST_LEN,
+            ),
+            DigestAlgo::Sha256 => Self::Sha256(Sha256::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Blake2b(hasher, _) => {
+                hasher.update(data);
+            }
+            Self::Sha256(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finalize_hex(self) -> anyhow::Result<String> {
+        match self {
+            Self::Blake2b(hasher, len) => Ok(hex::encode(
+                hasher
+                    .finalize()
+                    .as_bytes()
+                    .get(..len)
+                    .context("invalid digest length")?,
+            )),
+            Self::Sha256(hasher) => Ok(hex::encode(hasher.finalize())),
+        }
+    }
+}
+
+/// Computes the BLAKE2b-256 digest of the file, truncated to [`PROOF_DIGEST_LEN`] bytes. This is
+/// the digest used in [`DEFAULT_PARAMETERS`].
+///
+/// Hashing runs on a blocking thread. If the returned future is dropped, hashing stops after the
+/// current chunk rather than reading the rest of the file.
+pub(super) fn blake2b_digest(
+    path: &Path,
+    on_hashed: Option<HashProgressFn>,
+) -> BoxFuture<'static, anyhow::Result<String>> {
+    file_digest(path, on_hashed, DigestAlgo::TruncatedBlake2b, None)
 }
 
-/// Ensures the parameter file is downloaded and has the correct checksum.
+/// The default [`DigestFn`], i.e., [`blake2b_digest`]. Unlike custom digest functions, it runs on
+/// the hashing thread pool, if any, see [`spawn_hashing`].
+pub(super) static DEFAULT_DIGEST: Lazy<DigestFn> = Lazy::new(|| Arc::new(blake2b_digest));
+
+/// Runs the blocking hashing task on the given thread pool, or on the Tokio blocking pool if none.
+async fn spawn_hashing<T: Send + 'static>(
+    pool: Option<&Arc<ThreadPool>>,
+    task: impl FnOnce() -> T + Send + 'static,
+) -> anyhow::Result<T> {
+    match pool {
+        Some(pool) => {
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            pool.spawn(move || {
+                // The receiver is gone if the hashing was cancelled.
+                let _ = tx.send(task());
+            });
+            Ok(rx.await.context("hashing task panicked")?)
+        }
+        None => Ok(tokio::task::spawn_blocking(task).await?),
+    }
+}
+
+/// Computes the hex-encoded digest of the file with the given algorithm, as [`blake2b_digest`].
+fn file_digest(
+    path: &Path,
+    on_hashed: Option<HashProgressFn>,
+    algo: DigestAlgo,
+    pool: Option<&Arc<ThreadPool>>,
+) -> BoxFuture<'static, anyhow::Result<String>> {
+    let file = SyncFile::open(path);
+    let pool = pool.cloned();
+    async move {
+        let mut file = file?;
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let _guard = CancelOnDrop(cancelled.clone());
+        let hasher = spawn_hashing(pool.as_ref(), move || -> Result<FileHasher, io::Error> {
+            let mut hasher = FileHasher::new(algo);
+            let mut buf = vec![0; HASH_CHUNK_SIZE];
+            let mut hashed = 0;
+            loop {
+                if cancelled.load(Ordering::Relaxed) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Interrupted,
+                        "parameter file hashing cancelled",
+                    ));
+                }
+                let n = match file.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(n) => n,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                };
+                hasher.update(buf.get(..n).unwrap_or_default());
+                hashed += n as u64;
+                if let Some(on_hashed) = &on_hashed {
+                    on_hashed(hashed);
+                }
+            }
+            Ok(hasher)
+        })
+        .await??;
+        hasher.finalize_hex()
+    }
+    .boxed()
+}
+
+/// Ensures the parameter file is downloaded and has the correct checksum, as computed by `digest`.
 /// This behavior can be disabled by setting the [`FOREST_FORCE_TRUST_PARAMS_ENV`] environment variable to 1.
-pub(super) async fn check_parameter_file(path: &Path, info: &ParameterData) -> anyhow::Result<()> {
+pub(super) async fn check_parameter_file(
+    path: &Path,
+    info: &ParameterData,
+    digest: &DigestFn,
+    on_hashed: Option<HashProgressFn>,
+    pool: Option<&Arc<ThreadPool>>,
+) -> anyhow::Result<()> {
+    check_parameter_file_inner(
+        path,
+        info,
+        digest,
+        on_hashed,
+        is_env_truthy(FOREST_FORCE_TRUST_PARAMS_ENV),
+        pool,
+    )
+    .await
+}
+
+/// Like [`check_parameter_file`], but files larger than `trust_above` bytes, if any, are only
+/// checked for presence and size, e.g., to skip hashing the large `.params` files at startup
+/// while still verifying the small verification keys.
+pub(super) async fn check_parameter_file_trusting_above(
+    path: &Path,
+    info: &ParameterData,
+    digest: &DigestFn,
+    on_hashed: Option<HashProgressFn>,
+    trust_above: Option<u64>,
+    pool: Option<&Arc<ThreadPool>>,
+) -> anyhow::Result<()> {
+    let above_threshold = trust_above.is_some_and(|threshold| {
+        std::fs::metadata(path).is_ok_and(|metadata| metadata.len() > threshold)
+    });
+    if above_threshold {
+        debug!("Parameter file {path:?} is above the verification threshold, trusting it");
+    }
+    check_parameter_file_inner(
+        path,
+        info,
+        digest,
+        on_hashed,
+        above_threshold || is_env_truthy(FOREST_FORCE_TRUST_PARAMS_ENV),
+        pool,
+    )
+    .await
+}
+
+/// Like [`check_parameter_file`], but the cached digests, if any, are ignored and the file is
+/// always hashed, e.g., to catch the files corrupted in place without their size or modification
+/// time changing. A file found corrupt is evicted from the caches.
+pub(super) async fn check_parameter_file_uncached(
+    path: &Path,
+    info: &ParameterData,
+    digest: &DigestFn,
+    on_hashed: Option<HashProgressFn>,
+    pool: Option<&Arc<ThreadPool>>,
+) -> anyhow::Result<()> {
+    check_parameter_file_with(
+        path,
+        info,
+        digest,
+        on_hashed,
+        is_env_truthy(FOREST_FORCE_TRUST_PARAMS_ENV),
+        false,
+        pool,
+    )
+    .await
+}
+
+async fn check_parameter_file_inner(
+    path: &Path,
+    info: &ParameterData,
+    digest: &DigestFn,
+    on_hashed: Option<HashProgressFn>,
+    trust: bool,
+    pool: Option<&Arc<ThreadPool>>,
+) -> anyhow::Result<()> {
+    check_parameter_file_with(path, info, digest, on_hashed, trust, true, pool).await
+}
+
+async fn check_parameter_file_with(
+    path: &Path,
+    info: &ParameterData,
+    digest: &DigestFn,
+    on_hashed: Option<HashProgressFn>,
+    trust: bool,
+    // Only read by the optional digest caches.
+    #[cfg_attr(
+        not(any(all(unix, feature = "xattr"), feature = "digest-index")),
+        allow(unused_variables)
+    )]
+    use_cache: bool,
+    pool: Option<&Arc<ThreadPool>>,
+) -> anyhow::Result<()> {
+    // Even when trusted, the file must be present, otherwise it would never be downloaded.
+    // Empty files are reported explicitly rather than as a checksum mismatch.
+    let len = std::fs::metadata(path)
+        .map_err(|e| permission_error(path, e))?
+        .len();
+    if len == 0 {
+        return Err(ParamFetchError::EmptyFile(path.to_owned()).into());
+    }
+    // A file of the wrong size cannot match the digest, no need to hash it.
+    if let Some(expected) = info.size.filter(|expected| *expected != len) {
+        return Err(ParamFetchError::SizeMismatch {
+            path: path.to_owned(),
+            actual: len,
+            expected,
+        }
+        .into());
+    }
+
+    // Neither can an unreadable file be used, nor fixed by downloading it again.
+    std::fs::File::open(path).map_err(|e| permission_error(path, e))?;
+
+    if trust {
+        warn!("Assuming parameter files are okay. Do not use in production!");
+        return Ok(());
+    }
+
+    let expected = info.digest.to_hex();
+    #[cfg(all(unix, feature = "xattr"))]
+    if use_cache && digest_cache::get(path).is_some_and(|cached| expected.contains(&cached)) {
+        debug!("Parameter file {:?} is ok (cached digest)", path);
+        return Ok(());
+    }
+    #[cfg(feature = "digest-index")]
+    if use_cache && digest_index::contains(path, &expected, len) {
+        debug!("Parameter file {:?} is ok (indexed digest)", path);
+        return Ok(());
+    }
+
+    // A custom digest function replaces the built-in algorithms altogether. Otherwise, the
+    // algorithm is inferred from the length of the expected digests: full-length digests are
+    // tried as BLAKE2b-256 first, then as SHA-256.
+    let algos: &[Option<DigestAlgo>] = if Arc::ptr_eq(digest, &DEFAULT_DIGEST) {
+        &[
+            Some(DigestAlgo::TruncatedBlake2b),
+            Some(DigestAlgo::Blake2b256),
+            Some(DigestAlgo::Sha256),
+        ]
+    } else {
+        &[None]
+    };
+    let mut actual = vec![];
+    for algo in algos {
+        let hash = match algo {
+            Some(algo) => {
+                if !expected.iter().any(|e| e.len() == algo.hex_len()) {
+                    continue;
+                }
+                file_digest(path, on_hashed.clone(), *algo, pool).await?
+            }
+            None => {
+                let hash = digest(path, on_hashed.clone()).await?;
+                // A digest of another length cannot match, which is a configuration error
+                // rather than a corrupt file.
+                anyhow::ensure!(
+                    expected.iter().any(|e| e.len() == hash.len()),
+                    "the configured digest function returned {} hex characters for {path:?}, \
+                     but the manifest expects {}",
+                    hash.len(),
+                    expected
+                        .iter()
+                        .map(|e| e.len().to_string())
+                        .collect::<Vec<_>>()
+                        .join(" or ")
+                );
+                hash
+            }
+        };
+        if expected.iter().any(|e| hash.eq_ignore_ascii_case(e)) {
+            if let Some(sha256) = info.sha256 {
+                let expected = hex::encode(sha256.0);
+                let actual = file_digest(path, None, DigestAlgo::Sha256, pool).await?;
+                if !actual.eq_ignore_ascii_case(&expected) {
+                    return Err(ParamFetchError::ChecksumMismatch {
+                        path: path.to_owned(),
+                        actual,
+                        expected,
+                    }
+                    .into());
+                }
+            }
+            debug!("Parameter file {:?} is ok", path);
+            #[cfg(all(unix, feature = "xattr"))]
+            digest_cache::set(path, &hash.to_ascii_lowercase());
+            #[cfg(feature = "digest-index")]
+            digest_index::insert(path, &hash, len);
+            return Ok(());
+        }
+        actual.push(hash);
+    }
+    #[cfg(all(unix, feature = "xattr"))]
+    digest_cache::remove(path);
+    #[cfg(feature = "digest-index")]
+    digest_index::remove(path, &expected);
+    if let Some(chunks) = &info.chunks {
+        let corrupt = corrupt_chunks(path, chunks, pool).await?;
+        if !corrupt.is_empty() {
+            return Err(ParamFetchError::CorruptChunks {
+                path: path.to_owned(),
+                chunks: corrupt,
+            }
+            .into());
+        }
+    }
+    Err(ParamFetchError::ChecksumMismatch {
+        path: path.to_owned(),
+        actual: actual.join(" or "),
+        expected: expected.join(" or "),
+    }
+    .into())
+}
+
+/// Reports a permission error on the parameter file at `path` as
+/// [`ParamFetchError::PermissionDenied`], other errors as is.
+fn permission_error(path: &Path, e: io::Error) -> anyhow::Error {
+    if e.kind() == io::ErrorKind::PermissionDenied {
+        ParamFetchError::PermissionDenied {
+            path: path.to_owned(),
+        }
+        .into()
+    } else {
+        e.into()
+    }
+}
+
+/// Checks the parameter file stored in `store` like [`check_parameter_file`] does for the local
+/// files, reading it once. The digest algorithms are inferred from the manifest, custom
+/// [`DigestFn`]s work on local files only.
+pub(super) async fn check_stored_file(
+    store: &dyn ParamStore,
+    name: &str,
+    info: &ParameterData,
+) -> anyhow::Result<()> {
     if is_env_truthy(FOREST_FORCE_TRUST_PARAMS_ENV) {
+        if !store.exists(name).await? {
+            return Err(io::Error::from(io::ErrorKind::NotFound).into());
+        }
         warn!("Assuming parameter files are okay. Do not use in production!");
         return Ok(());
     }
+    check_reader(store.open_reader(name).await?, name, info).await
+}
+
+/// Checks the content of the parameter file `name` read from `reader` against its manifest entry,
+/// e.g., the bytes piped on stdin. The digest algorithms are inferred from the manifest.
+pub(super) async fn check_reader(
+    mut reader: impl futures::AsyncRead + Unpin,
+    name: &str,
+    info: &ParameterData,
+) -> anyhow::Result<()> {
+    let path = PathBuf::from(name);
+    let expected = info.digest.to_hex();
+    let mut hashers: Vec<_> = [
+        DigestAlgo::TruncatedBlake2b,
+        DigestAlgo::Blake2b256,
+        DigestAlgo::Sha256,
+    ]
+    .into_iter()
+    .filter(|algo| expected.iter().any(|e| e.len() == algo.hex_len()))
+    .map(FileHasher::new)
+    .collect();
+    let mut sha256 = info.sha256.map(|_| FileHasher::new(DigestAlgo::Sha256));
+    let mut buf = vec![0; HASH_CHUNK_SIZE];
+    let mut len = 0;
+    loop {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        let chunk = buf.get(..n).unwrap_or_default();
+        for hasher in hashers.iter_mut().chain(&mut sha256) {
+            hasher.update(chunk);
+        }
+        len += n as u64;
+    }
 
-    let hash = tokio::task::spawn_blocking({
-        let file = SyncFile::open(path)?;
-        move || -> Result<Hash, io::Error> {
-            let mut reader = SyncBufReader::new(file);
-            let mut hasher = Blake2b::new();
-            sync_copy(&mut reader, &mut hasher)?;
-            Ok(hasher.finalize())
+    if len == 0 {
+        return Err(ParamFetchError::EmptyFile(path).into());
+    }
+    if let Some(expected) = info.size.filter(|expected| *expected != len) {
+        return Err(ParamFetchError::SizeMismatch {
+            path,
+            actual: len,
+            expected,
+        }
+        .into());
+    }
+    let mut actual = vec![];
+    for hasher in hashers {
+        let hash = hasher.finalize_hex()?;
+        if expected.iter().any(|e| hash.eq_ignore_ascii_case(e)) {
+            if let (Some(hasher), Some(expected)) = (sha256, info.sha256) {
+                let expected = hex::encode(expected.0);
+                let actual = hasher.finalize_hex()?;
+                if !actual.eq_ignore_ascii_case(&expected) {
+                    return Err(ParamFetchError::ChecksumMismatch {
+                        path,
+                        actual,
+                        expected,
+                    }
+                    .into());
+                }
+            }
+            debug!("Parameter file {name} is ok");
+            return Ok(());
         }
+        actual.push(hash);
+    }
+    Err(ParamFetchError::ChecksumMismatch {
+        path,
+        actual: actual.join(" or "),
+        expected: expected.join(" or "),
+    }
+    .into())
+}
+
+/// Returns the indices of the chunks of the parameter file not matching their digests. This works
+/// on partially downloaded files too: the chunks cut short by the end of the file, or past it, are
+/// reported as corrupt.
+pub(super) async fn corrupt_chunks(
+    path: &Path,
+    chunks: &ParamChunks,
+    pool: Option<&Arc<ThreadPool>>,
+) -> anyhow::Result<Vec<usize>> {
+    anyhow::ensure!(
+        chunks.size > 0,
+        "parameter file chunk size must be positive"
+    );
+    let mut file = SyncFile::open(path)?;
+    let chunks = chunks.clone();
+    let corrupt = spawn_hashing(pool, move || -> Result<Vec<usize>, io::Error> {
+        let mut corrupt = vec![];
+        let mut buf = vec![0; HASH_CHUNK_SIZE];
+        for (index, expected) in chunks.digests.iter().enumerate() {
+            let mut hasher = FileHasher::new(match expected {
+                ParamDigest::Truncated(_) => DigestAlgo::TruncatedBlake2b,
+                ParamDigest::Full(_) => DigestAlgo::Blake2b256,
+            });
+            let mut chunk = (&mut file).take(chunks.size);
+            loop {
+                let n = match chunk.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(n) => n,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                };
+                hasher.update(buf.get(..n).unwrap_or_default());
+            }
+            let actual = hasher.finalize_hex().map_err(io::Error::other)?;
+            if !actual.eq_ignore_ascii_case(&expected.to_hex()) {
+                corrupt.push(index);
+            }
+        }
+        Ok(corrupt)
     })
     .await??;
+    Ok(corrupt)
+}
 
-    let hash_chunk = hash
-        .as_bytes()
-        .get(..PROOF_DIGEST_LEN)
-        .context("invalid digest length")?;
-    if info.digest == hash_chunk {
-        debug!("Parameter file {:?} is ok", path);
-        Ok(())
-    } else {
-        bail!(
-            "Checksum mismatch in param file {:?}. ({:x?} != {:x?})",
-            path,
-            hash_chunk,
-            info.digest,
-        )
+/// Caches the verified digest of a parameter file in its extended attributes, along with its
+/// modification time, so that unchanged files are not re-hashed on every start. Failures, e.g.,
+/// on file systems without extended attribute support, are ignored and the file is hashed.
+#[cfg(all(unix, feature = "xattr"))]
+mod digest_cache {
+    use std::{path::Path, time::UNIX_EPOCH};
+
+    use tracing::debug;
+
+    /// Extended attribute holding `<digest>:<mtime in nanoseconds>`.
+    const DIGEST_XATTR: &str = "user.forest.digest";
+
+    fn mtime_nanos(path: &Path) -> Option<u128> {
+        let mtime = std::fs::metadata(path).ok()?.modified().ok()?;
+        Some(mtime.duration_since(UNIX_EPOCH).ok()?.as_nanos())
+    }
+
+    /// Returns the cached digest, if the file has not been modified since it was verified.
+    pub(super) fn get(path: &Path) -> Option<String> {
+        let value = String::from_utf8(xattr::get(path, DIGEST_XATTR).ok()??).ok()?;
+        let (digest, mtime) = value.split_once(':')?;
+        (mtime.parse::<u128>().ok()? == mtime_nanos(path)?).then(|| digest.to_owned())
+    }
+
+    /// Records the verified digest of the file.
+    pub(super) fn set(path: &Path, digest: &str) {
+        let Some(mtime) = mtime_nanos(path) else {
+            return;
+        };
+        if let Err(e) = xattr::set(path, DIGEST_XATTR, format!("{digest}:{mtime}").as_bytes()) {
+            debug!("Failed to cache the digest of {}: {e}", path.display());
+        }
+    }
+
+    /// Forgets the verified digest of the file, if any.
+    pub(super) fn remove(path: &Path) {
+        if let Err(e) = xattr::remove(path, DIGEST_XATTR) {
+            debug!(
+                "Failed to remove the cached digest of {}: {e}",
+                path.display()
+            );
+        }
+    }
+}
+
+/// Caches the verified digests of the parameter files in an index file of their directory, along
+/// with their sizes, so that unchanged files are not re-hashed on every start. Unlike the extended
+/// attributes cache, the entries are keyed on the digest and size rather than on the path and
+/// modification time, so that they survive moving the cache directory along with the index.
+/// Failures are ignored and the file is hashed.
+#[cfg(feature = "digest-index")]
+mod digest_index {
+    use std::{
+        collections::BTreeMap,
+        path::{Path, PathBuf},
+    };
+
+    use once_cell::sync::Lazy;
+    use parking_lot::Mutex;
+    use tracing::debug;
+
+    /// Name of the index file, mapping the verified digests to the sizes of the files.
+    pub(super) const INDEX_FILE: &str = "verified-digests.json";
+
+    /// Serializes the updates of the index files.
+    static LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+    fn index_path(path: &Path) -> Option<PathBuf> {
+        Some(path.parent()?.join(INDEX_FILE))
+    }
+
+    fn read(index: &Path) -> BTreeMap<String, u64> {
+        std::fs::read(index)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    /// Returns whether a file of the given size and one of the given digests was verified.
+    pub(super) fn contains(path: &Path, digests: &[String], size: u64) -> bool {
+        let Some(index) = index_path(path) else {
+            return false;
+        };
+        let verified = read(&index);
+        digests
+            .iter()
+            .any(|digest| verified.get(&digest.to_ascii_lowercase()) == Some(&size))
+    }
+
+    /// Records the verified digest and size of the file.
+    pub(super) fn insert(path: &Path, digest: &str, size: u64) {
+        let Some(index) = index_path(path) else {
+            return;
+        };
+        let _guard = LOCK.lock();
+        let mut verified = read(&index);
+        if verified.insert(digest.to_ascii_lowercase(), size) == Some(size) {
+            return;
+        }
+        write(&index, &verified);
+    }
+
+    /// Forgets the given digests of the file, e.g., once it is found corrupt.
+    pub(super) fn remove(path: &Path, digests: &[String]) {
+        let Some(index) = index_path(path) else {
+            return;
+        };
+        let _guard = LOCK.lock();
+        let mut verified = read(&index);
+        let len = verified.len();
+        for digest in digests {
+            verified.remove(&digest.to_ascii_lowercase());
+        }
+        if verified.len() != len {
+            write(&index, &verified);
+        }
+    }
+
+    /// Replaces the index atomically, so that an interrupted update does not lose the other
+    /// entries.
+    fn write(index: &Path, verified: &BTreeMap<String, u64>) {
+        let tmp = index.with_extension("json.tmp");
+        if let Err(e) = serde_json::to_vec_pretty(verified)
+            .map_err(std::io::Error::other)
+            .and_then(|bytes| std::fs::write(&tmp, bytes))
+            .and_then(|()| std::fs::rename(&tmp, index))
+        {
+            debug!("Failed to update the digest index {}: {e}", index.display());
+        }
     }
 }
 
 // Proof parameter file directory. Defaults to
 // %DATA_DIR/filecoin-proof-parameters unless the FIL_PROOFS_PARAMETER_CACHE
-// environment variable is set.
+// environment variable is set. The name of the subdirectory can be changed with the
+// FOREST_PROOFS_PARAM_DIR_NAME environment variable.
 pub(super) fn param_dir(data_dir: &Path) -> PathBuf {
-    std::env::var(PathBuf::from(PROOFS_PARAMETER_CACHE_ENV))
+    param_dir_with(data_dir, |name| std::env::var(name).ok())
+}
+
+fn param_dir_with(data_dir: &Path, env: impl Fn(&str) -> Option<String>) -> PathBuf {
+    env(PROOFS_PARAMETER_CACHE_ENV)
         .map(PathBuf::from)
-        .unwrap_or_else(|_| data_dir.join(PARAM_DIR))
+        .unwrap_or_else(|| {
+            let name = env(PARAM_DIR_NAME_ENV).filter(|name| !name.is_empty());
+            data_dir.join(name.as_deref().unwrap_or(PARAM_DIR))
+        })
+}
+
+/// Returns [`param_dir`] with the symbolic links resolved, so that the cache directory can be
+/// relocated with a symlink. A missing directory is returned as is, to be created, but a dangling
+/// symlink is reported as [`ParamFetchError::DanglingCacheDir`] rather than failing later on.
+pub(super) fn resolved_param_dir(data_dir: &Path) -> anyhow::Result<PathBuf> {
+    let dir = param_dir(data_dir);
+    match std::fs::canonicalize(&dir) {
+        Ok(resolved) => Ok(resolved),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => match std::fs::read_link(&dir) {
+            Ok(target) => Err(ParamFetchError::DanglingCacheDir { path: dir, target }.into()),
+            Err(_) => Ok(dir),
+        },
+        Err(e) => Err(anyhow::Error::new(e).context(format!(
+            "failed to resolve parameter cache directory {}",
+            dir.display()
+        ))),
+    }
 }
 
 /// Forest uses a set of external crates for verifying the proofs generated by
@@ -112,6 +1076,104 @@ pub fn set_proofs_parameter_cache_dir_env(data_dir: &Path) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::proofs_api::test_params;
+    use std::sync::atomic::AtomicU64;
+
+    #[test]
+    fn test_param_dir_name() {
+        let data_dir = Path::new("/var/lib/forest");
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+
+        assert_eq!(param_dir_with(data_dir, env(&[])), data_dir.join(PARAM_DIR));
+        assert_eq!(
+            param_dir_with(data_dir, env(&[(PARAM_DIR_NAME_ENV, "proof-params")])),
+            data_dir.join("proof-params")
+        );
+        assert_eq!(
+            param_dir_with(data_dir, env(&[(PARAM_DIR_NAME_ENV, "")])),
+            data_dir.join(PARAM_DIR)
+        );
+        // The whole path takes precedence over the subdirectory name.
+        assert_eq!(
+            param_dir_with(
+                data_dir,
+                env(&[
+                    (PARAM_DIR_NAME_ENV, "proof-params"),
+                    (PROOFS_PARAMETER_CACHE_ENV, "/var/tmp/params"),
+                ])
+            ),
+            PathBuf::from("/var/tmp/params")
+        );
+    }
+
+    fn default_digest() -> DigestFn {
+        DEFAULT_DIGEST.clone()
+    }
+
+    #[test]
+    fn test_default_parameters_valid() {
+        use crate::shim::sector::SectorSize;
+
+        let known_sizes = [
+            0,
+            SectorSize::_2KiB as u64,
+            SectorSize::_8MiB as u64,
+            SectorSize::_512MiB as u64,
+            SectorSize::_32GiB as u64,
+            SectorSize::_64GiB as u64,
+        ];
+        let manifest = parse_manifest(DEFAULT_PARAMETERS).unwrap();
+        for (name, info) in &manifest {
+            assert!(
+                ParamFileKind::from_name(name).is_some(),
+                "{name}: unknown kind"
+            );
+            assert_ne!(info.cid, Cid::default(), "{name}: empty CID");
+            let digests = info.digest.to_hex();
+            assert!(!digests.is_empty(), "{name}: no digest");
+            for digest in digests {
+                assert!(digest.len() >= 32, "{name}: short digest {digest}");
+            }
+            assert!(
+                known_sizes.contains(&info.sector_size),
+                "{name}: unknown sector size {}",
+                info.sector_size
+            );
+        }
+    }
+
+    #[test]
+    fn test_param_file_kind() {
+        let manifest = parse_manifest(DEFAULT_PARAMETERS).unwrap();
+        let count = |kind| {
+            manifest
+                .keys()
+                .filter(|name| ParamFileKind::from_name(name) == Some(kind))
+                .count()
+        };
+        assert_eq!(count(ParamFileKind::Params), 22);
+        assert_eq!(count(ParamFileKind::VerifyingKey), 22);
+        assert_eq!(count(ParamFileKind::Srs), 1);
+        assert_eq!(
+            ParamFileKind::from_name("v28-fil-inner-product-v1.srs"),
+            Some(ParamFileKind::Srs)
+        );
+        assert_eq!(
+            ParamFileKind::from_name("v28-test.vk"),
+            Some(ParamFileKind::VerifyingKey)
+        );
+        assert_eq!(ParamFileKind::from_name("v28-proof.params.tmp"), None);
+        assert_eq!(ParamFileKind::from_name("params"), None);
+        assert!(!ParamFileKind::Params.is_verification());
+        assert!(ParamFileKind::VerifyingKey.is_verification());
+        assert!(ParamFileKind::Srs.is_verification());
+    }
 
     #[tokio::test]
     async fn test_proof_file_check() {
@@ -132,26 +1194,680 @@ mod tests {
 
         let param_data = ParameterData {
             cid: Cid::default(),
-            digest: digest.try_into().unwrap(),
+            digest: <[u8; PROOF_DIGEST_LEN]>::try_from(digest).unwrap().into(),
             sector_size: 32,
+            size: None,
+            optional: false,
+            chunks: None,
+            sha256: None,
         };
 
-        check_parameter_file(path, &param_data).await.unwrap()
+        check_parameter_file(path, &param_data, &default_digest(), None, None)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_proof_file_check_hash_pool() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("v28-test.vk");
+        let data = test_params::CONTENT;
+        std::fs::write(&path, data).unwrap();
+        let digest = <[u8; PROOF_DIGEST_LEN]>::try_from(
+            blake2b_simd::blake2b(data)
+                .as_bytes()
+                .get(..PROOF_DIGEST_LEN)
+                .unwrap(),
+        )
+        .unwrap();
+        let param_data = ParameterData {
+            cid: Cid::default(),
+            digest: digest.into(),
+            sector_size: 2048,
+            size: None,
+            optional: false,
+            chunks: None,
+            sha256: None,
+        };
+        let pool = Arc::new(
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(1)
+                .thread_name(|i| format!("param-hash-{i}"))
+                .build()
+                .unwrap(),
+        );
+        // The progress is reported from the hashing thread.
+        let threads = Arc::new(parking_lot::Mutex::new(vec![]));
+        let on_hashed: HashProgressFn = {
+            let threads = threads.clone();
+            Arc::new(move |_| {
+                threads
+                    .lock()
+                    .push(std::thread::current().name().map(str::to_owned));
+            })
+        };
+
+        check_parameter_file_inner(
+            &path,
+            &param_data,
+            &DEFAULT_DIGEST,
+            Some(on_hashed),
+            false,
+            Some(&pool),
+        )
+        .await
+        .unwrap();
+        let threads = threads.lock().clone();
+        assert!(!threads.is_empty());
+        assert!(threads
+            .iter()
+            .all(|name| name.as_deref() == Some("param-hash-0")));
+    }
+
+    #[tokio::test]
+    async fn test_proof_file_check_corrupt_chunk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("v28-test.vk");
+        let data = test_params::CONTENT;
+        let chunks: Vec<_> = data.chunks(16).map(test_params::digest).collect();
+        assert_eq!(chunks.len(), 4);
+        let param_data: ParameterData = serde_json::from_value(serde_json::json!({
+            "cid": test_params::CID,
+            "digest": test_params::digest(data),
+            "sector_size": 2048,
+            "chunks": { "size": 16, "digests": chunks },
+        }))
+        .unwrap();
+        let chunks = param_data.chunks.as_ref().unwrap();
+
+        std::fs::write(&path, data).unwrap();
+        check_parameter_file(&path, &param_data, &default_digest(), None, None)
+            .await
+            .unwrap();
+        assert!(corrupt_chunks(&path, chunks, None)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let mut corrupt = data.to_vec();
+        *corrupt.get_mut(20).unwrap() ^= 1;
+        std::fs::write(&path, &corrupt).unwrap();
+        let err = check_parameter_file(&path, &param_data, &default_digest(), None, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ParamFetchError>(),
+            Some(ParamFetchError::CorruptChunks { chunks, .. }) if *chunks == [1]
+        ));
+
+        // The chunks of a partial download are verified up to the end of the file.
+        std::fs::write(&path, data.get(..40).unwrap()).unwrap();
+        assert_eq!(corrupt_chunks(&path, chunks, None).await.unwrap(), [2, 3]);
     }
 
     #[tokio::test]
     async fn test_proof_file_check_no_file() {
         let param_data = ParameterData {
             cid: Cid::default(),
-            digest: [0; PROOF_DIGEST_LEN],
+            digest: [0; PROOF_DIGEST_LEN].into(),
             sector_size: 32,
+            size: None,
+            optional: false,
+            chunks: None,
+            sha256: None,
         };
 
         let path = Path::new("cthulhuazathoh.dagon");
-        let ret = check_parameter_file(path, &param_data).await;
+        let ret = check_parameter_file(path, &param_data, &default_digest(), None, None).await;
+        assert_eq!(
+            ret.unwrap_err().downcast_ref::<io::Error>().unwrap().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+
+    const TEST_MANIFEST_ENTRY: &str = r#""v28-test.vk": {
+        "cid": "QmUa7f9JtJMsqJJ3s3ZXk6WyF4xJLE8FiqYskZGgk8GCDv",
+        "digest": "994c5b7d450ca9da348c910689f2dc7f",
+        "sector_size": 2048
+    }"#;
+
+    #[test]
+    fn test_parse_manifest_supported_version() {
+        let manifest =
+            parse_manifest(&format!(r#"{{"version": 1, {TEST_MANIFEST_ENTRY}}}"#)).unwrap();
+        assert_eq!(manifest.len(), 1);
+        assert!(manifest.contains_key("v28-test.vk"));
+    }
+
+    #[test]
+    fn test_parse_manifest_no_version() {
+        let manifest = parse_manifest(&format!("{{{TEST_MANIFEST_ENTRY}}}")).unwrap();
+        assert_eq!(manifest.len(), 1);
+
+        assert!(!parse_manifest(DEFAULT_PARAMETERS).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_manifest_future_version() {
+        let err =
+            parse_manifest(&format!(r#"{{"version": 999, {TEST_MANIFEST_ENTRY}}}"#)).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ParamFetchError>(),
+            Some(ParamFetchError::UnsupportedManifestVersion(999))
+        ));
+    }
+
+    #[test]
+    fn test_manifest_sector_sizes() {
+        assert_eq!(
+            manifest_sector_sizes(DEFAULT_PARAMETERS).unwrap(),
+            BTreeSet::from([0, 2048, 8388608, 536870912, 34359738368, 68719476736])
+        );
+    }
+
+    #[test]
+    fn test_diff_manifests() {
+        let old = r#"{
+            "v28-kept.vk": {"cid": "QmUa7f9JtJMsqJJ3s3ZXk6WyF4xJLE8FiqYskZGgk8GCDv", "digest": "fc3d0cd4ef1ae4b8a3b57fd5e0e8e6b1", "sector_size": 2048},
+            "v28-removed.vk": {"cid": "QmUa7f9JtJMsqJJ3s3ZXk6WyF4xJLE8FiqYskZGgk8GCDv", "digest": "fc3d0cd4ef1ae4b8a3b57fd5e0e8e6b1", "sector_size": 2048},
+            "v28-rehashed.vk": {"cid": "QmUa7f9JtJMsqJJ3s3ZXk6WyF4xJLE8FiqYskZGgk8GCDv", "digest": "fc3d0cd4ef1ae4b8a3b57fd5e0e8e6b1", "sector_size": 2048}
+        }"#;
+        let new = r#"{
+            "v28-kept.vk": {"cid": "QmUa7f9JtJMsqJJ3s3ZXk6WyF4xJLE8FiqYskZGgk8GCDv", "digest": "fc3d0cd4ef1ae4b8a3b57fd5e0e8e6b1", "sector_size": 2048},
+            "v28-added.vk": {"cid": "QmUa7f9JtJMsqJJ3s3ZXk6WyF4xJLE8FiqYskZGgk8GCDv", "digest": "fc3d0cd4ef1ae4b8a3b57fd5e0e8e6b1", "sector_size": 2048},
+            "v28-rehashed.vk": {"cid": "Qma5WL6abSqYg9uUQAZ3EHS286bsNsha7oAGsJBD48Bq2q", "digest": "1d8a0d2e6b5d4e3f2a1b0c9d8e7f6a5b", "sector_size": 2048}
+        }"#;
+
+        assert_eq!(
+            diff_manifests(old, new).unwrap(),
+            ManifestDiff {
+                added: vec!["v28-added.vk".into()],
+                removed: vec!["v28-removed.vk".into()],
+                changed: vec![ManifestChange {
+                    name: "v28-rehashed.vk".into(),
+                    fields: vec!["cid", "digest"],
+                }],
+            }
+        );
+        assert_eq!(diff_manifests(old, old).unwrap(), ManifestDiff::default());
+        assert_eq!(
+            diff_manifests(DEFAULT_PARAMETERS, DEFAULT_PARAMETERS).unwrap(),
+            ManifestDiff::default()
+        );
+    }
+
+    #[test]
+    fn test_manifest_digest_canonical() {
+        let compact = r#"{"v28-b.vk":{"cid":"QmUa7f9JtJMsqJJ3s3ZXk6WyF4xJLE8FiqYskZGgk8GCDv","digest":"fc3d0cd4ef1ae4b8a3b57fd5e0e8e6b1","sector_size":2048},"v28-a.vk":{"cid":"Qma5WL6abSqYg9uUQAZ3EHS286bsNsha7oAGsJBD48Bq2q","digest":"1d8a0d2e6b5d4e3f2a1b0c9d8e7f6a5b","sector_size":0}}"#;
+        let pretty = r#"
+        {
+            "v28-a.vk": {
+                "sector_size": 0,
+                "digest": "1d8a0d2e6b5d4e3f2a1b0c9d8e7f6a5b",
+                "cid": "Qma5WL6abSqYg9uUQAZ3EHS286bsNsha7oAGsJBD48Bq2q"
+            },
+            "v28-b.vk": {
+                "cid": "QmUa7f9JtJMsqJJ3s3ZXk6WyF4xJLE8FiqYskZGgk8GCDv",
+                "digest": "fc3d0cd4ef1ae4b8a3b57fd5e0e8e6b1",
+                "sector_size": 2048
+            }
+        }"#;
+        assert_eq!(
+            manifest_digest(compact).unwrap(),
+            manifest_digest(pretty).unwrap()
+        );
+        let other = compact.replace("2048", "8388608");
+        assert_ne!(
+            manifest_digest(compact).unwrap(),
+            manifest_digest(&other).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_proof_file_check_trusted() {
+        let param_data = ParameterData {
+            cid: Cid::default(),
+            digest: [0; PROOF_DIGEST_LEN].into(),
+            sector_size: 32,
+            size: None,
+            optional: false,
+            chunks: None,
+            sha256: None,
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("v28-test.vk");
+
+        // A missing file is reported as such, so that it gets downloaded.
+        let ret =
+            check_parameter_file_inner(&path, &param_data, &default_digest(), None, true, None)
+                .await;
         assert_eq!(
             ret.unwrap_err().downcast_ref::<io::Error>().unwrap().kind(),
             io::ErrorKind::NotFound
         );
+
+        std::fs::write(&path, b"").unwrap();
+        let err =
+            check_parameter_file_inner(&path, &param_data, &default_digest(), None, true, None)
+                .await
+                .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ParamFetchError>(),
+            Some(ParamFetchError::EmptyFile(_))
+        ));
+
+        // The checksum is not verified.
+        std::fs::write(&path, b"Ph'nglui mglw'nafh").unwrap();
+        check_parameter_file_inner(&path, &param_data, &default_digest(), None, true, None)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_verify_manifest_signature() {
+        let private_key = bls_signatures::PrivateKey::generate(&mut rand::thread_rng());
+        let signature = ManifestSignature {
+            signature: private_key.sign(DEFAULT_PARAMETERS).as_bytes(),
+            public_key: private_key.public_key().as_bytes(),
+        };
+        verify_manifest_signature(DEFAULT_PARAMETERS, &signature).unwrap();
+
+        let tampered = DEFAULT_PARAMETERS.replacen("Qm", "Qn", 1);
+        let err = verify_manifest_signature(&tampered, &signature).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ParamFetchError>(),
+            Some(ParamFetchError::InvalidManifestSignature)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_proof_file_check_multiple_digests() {
+        let tempfile = tempfile::Builder::new().tempfile().unwrap();
+        let path = tempfile.path();
+        std::fs::write(path, b"Cthulhu fhtagn!").unwrap();
+        let actual = blake2b_digest(path, None).await.unwrap();
+        let other = hex::encode([0; PROOF_DIGEST_LEN]);
+
+        let single: ParameterData = serde_json::from_str(&format!(
+            r#"{{"cid": "QmUa7f9JtJMsqJJ3s3ZXk6WyF4xJLE8FiqYskZGgk8GCDv", "digest": "{actual}", "sector_size": 2048}}"#
+        ))
+        .unwrap();
+        assert!(matches!(single.digest, ParamDigests::Single(_)));
+        check_parameter_file_inner(path, &single, &default_digest(), None, false, None)
+            .await
+            .unwrap();
+
+        let multiple: ParameterData = serde_json::from_str(&format!(
+            r#"{{"cid": "QmUa7f9JtJMsqJJ3s3ZXk6WyF4xJLE8FiqYskZGgk8GCDv", "digest": ["{other}", "{actual}"], "sector_size": 2048}}"#
+        ))
+        .unwrap();
+        assert!(matches!(&multiple.digest, ParamDigests::Multiple(d) if d.len() == 2));
+        check_parameter_file_inner(path, &multiple, &default_digest(), None, false, None)
+            .await
+            .unwrap();
+
+        let mismatch: ParameterData = serde_json::from_str(&format!(
+            r#"{{"cid": "QmUa7f9JtJMsqJJ3s3ZXk6WyF4xJLE8FiqYskZGgk8GCDv", "digest": ["{other}"], "sector_size": 2048}}"#
+        ))
+        .unwrap();
+        let err = check_parameter_file_inner(path, &mismatch, &default_digest(), None, false, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ParamFetchError>(),
+            Some(ParamFetchError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_blake2b_digest_progress() {
+        let tempfile = tempfile::Builder::new().tempfile().unwrap();
+        let path = tempfile.path();
+        let len = 2 * HASH_CHUNK_SIZE + 42;
+        std::fs::write(path, vec![0xaa; len]).unwrap();
+
+        let reported = Arc::new(parking_lot::Mutex::new(vec![]));
+        let on_hashed: HashProgressFn = {
+            let reported = reported.clone();
+            Arc::new(move |hashed| reported.lock().push(hashed))
+        };
+        let digest = blake2b_digest(path, Some(on_hashed)).await.unwrap();
+        assert_eq!(digest, blake2b_digest(path, None).await.unwrap());
+
+        let reported = reported.lock();
+        assert!(reported.len() > 1);
+        assert!(reported.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(reported.last().copied(), Some(len as u64));
+    }
+
+    #[tokio::test]
+    async fn test_build_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("v28-test.vk"), b"Cthulhu fhtagn!").unwrap();
+        std::fs::write(dir.path().join("v28-test.params"), b"Ph'nglui mglw'nafh").unwrap();
+        std::fs::create_dir(dir.path().join("nested")).unwrap();
+        // Files other than the parameter files are left out.
+        for name in [
+            "params.lock",
+            ".no-download",
+            "v28-test.vk.corrupt.1700000000",
+            "verified-digests.json",
+        ] {
+            std::fs::write(dir.path().join(name), b"Cthulhu fhtagn!").unwrap();
+        }
+
+        let known = format!("{{{TEST_MANIFEST_ENTRY}}}");
+        let manifest = parse_manifest(&build_manifest(dir.path(), &known).await.unwrap()).unwrap();
+        assert_eq!(manifest.len(), 2);
+
+        let expected = parse_manifest(&known).unwrap();
+        let expected = expected.get("v28-test.vk").unwrap();
+        let vk = manifest.get("v28-test.vk").unwrap();
+        assert_eq!(vk.cid, expected.cid);
+        assert_eq!(vk.sector_size, expected.sector_size);
+
+        assert_eq!(vk.size, Some(15));
+
+        let params = manifest.get("v28-test.params").unwrap();
+        assert_eq!(params.cid, Cid::default());
+        assert_eq!(params.sector_size, 0);
+        assert_eq!(params.size, Some(18));
+
+        for (name, info) in manifest {
+            check_parameter_file_inner(
+                &dir.path().join(name),
+                &info,
+                &default_digest(),
+                None,
+                false,
+                None,
+            )
+            .await
+            .unwrap();
+        }
+    }
+
+    #[cfg(all(unix, feature = "xattr"))]
+    #[tokio::test]
+    async fn test_proof_file_check_cached_digest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("v28-test.vk");
+        std::fs::write(&path, b"Cthulhu fhtagn!").unwrap();
+        if !xattr::SUPPORTED_PLATFORM || xattr::set(&path, "user.forest.test", b"").is_err() {
+            // Extended attributes are not supported by the file system.
+            return;
+        }
+        let actual = blake2b_digest(&path, None).await.unwrap();
+        let param_data = ParameterData {
+            cid: Cid::default(),
+            digest: <[u8; PROOF_DIGEST_LEN]>::try_from(hex::decode(&actual).unwrap())
+                .unwrap()
+                .into(),
+            sector_size: 2048,
+            size: None,
+            optional: false,
+            chunks: None,
+            sha256: None,
+        };
+
+        let calls = Arc::new(AtomicU64::new(0));
+        let counting_digest: DigestFn = {
+            let calls = calls.clone();
+            Arc::new(move |path, on_hashed| {
+                calls.fetch_add(1, Ordering::Relaxed);
+                blake2b_digest(path, on_hashed)
+            })
+        };
+        check_parameter_file_inner(&path, &param_data, &counting_digest, None, false, None)
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+        // The second check is served from the cached digest, without reading the file.
+        check_parameter_file_inner(&path, &param_data, &counting_digest, None, false, None)
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+        // Modifying the file invalidates the cached digest.
+        std::fs::write(&path, b"Ph'nglui mglw'nafh").unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(1))
+            .unwrap();
+        check_parameter_file_inner(&path, &param_data, &counting_digest, None, false, None)
+            .await
+            .unwrap_err();
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[cfg(feature = "digest-index")]
+    #[tokio::test]
+    async fn test_proof_file_check_indexed_digest() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_dir = dir.path().join("cache");
+        std::fs::create_dir(&cache_dir).unwrap();
+        let path = cache_dir.join("v28-test.vk");
+        std::fs::write(&path, b"Cthulhu fhtagn!").unwrap();
+        let actual = blake2b_digest(&path, None).await.unwrap();
+        let param_data = ParameterData {
+            cid: Cid::default(),
+            digest: <[u8; PROOF_DIGEST_LEN]>::try_from(hex::decode(&actual).unwrap())
+                .unwrap()
+                .into(),
+            sector_size: 2048,
+            size: None,
+            optional: false,
+            chunks: None,
+            sha256: None,
+        };
+
+        let calls = Arc::new(AtomicU64::new(0));
+        let counting_digest: DigestFn = {
+            let calls = calls.clone();
+            Arc::new(move |path, on_hashed| {
+                calls.fetch_add(1, Ordering::Relaxed);
+                blake2b_digest(path, on_hashed)
+            })
+        };
+        check_parameter_file_inner(&path, &param_data, &counting_digest, None, false, None)
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+        // Moving the cache directory along with the index does not require hashing the file
+        // again, even though its modification time changes.
+        let moved_dir = dir.path().join("moved");
+        std::fs::rename(&cache_dir, &moved_dir).unwrap();
+        let path = moved_dir.join("v28-test.vk");
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(1))
+            .unwrap();
+        check_parameter_file_inner(&path, &param_data, &counting_digest, None, false, None)
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+        // A file of a different size is hashed again.
+        std::fs::write(&path, b"Ph'nglui mglw'nafh").unwrap();
+        check_parameter_file_inner(&path, &param_data, &counting_digest, None, false, None)
+            .await
+            .unwrap_err();
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_expected_params_version() {
+        assert_eq!(
+            expected_params_version(),
+            manifest_digest(DEFAULT_PARAMETERS).unwrap()
+        );
+        // Stable across calls.
+        assert_eq!(expected_params_version(), expected_params_version());
+    }
+
+    #[tokio::test]
+    async fn test_proof_file_check_sha256() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("v28-test.vk");
+        let data = b"Cthulhu fhtagn!";
+        std::fs::write(&path, data).unwrap();
+        let digest = hex::encode(
+            blake2b_simd::blake2b(data)
+                .as_bytes()
+                .get(..PROOF_DIGEST_LEN)
+                .unwrap(),
+        );
+        let param_data = |sha256: &str| -> ParameterData {
+            serde_json::from_str(&format!(
+                r#"{{"cid": "QmUa7f9JtJMsqJJ3s3ZXk6WyF4xJLE8FiqYskZGgk8GCDv", "digest": "{digest}", "sha256": "{sha256}", "sector_size": 2048}}"#
+            ))
+            .unwrap()
+        };
+
+        let sha256 = hex::encode(Sha256::digest(data));
+        let info = param_data(&sha256);
+        check_parameter_file_inner(&path, &info, &default_digest(), None, false, None)
+            .await
+            .unwrap();
+        check_reader(futures::io::Cursor::new(data), "v28-test.vk", &info)
+            .await
+            .unwrap();
+
+        // Matching the BLAKE2b digest only is not enough.
+        let info = param_data(&"00".repeat(FULL_DIGEST_LEN));
+        let err = check_parameter_file_inner(&path, &info, &default_digest(), None, false, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ParamFetchError>(),
+            Some(ParamFetchError::ChecksumMismatch { actual, .. }) if *actual == sha256
+        ));
+        let err = check_reader(futures::io::Cursor::new(data), "v28-test.vk", &info)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ParamFetchError>(),
+            Some(ParamFetchError::ChecksumMismatch { actual, .. }) if *actual == sha256
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_proof_file_check_digest_length() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("v28-test.vk");
+        let data = b"Cthulhu fhtagn!";
+        std::fs::write(&path, data).unwrap();
+        let blake2b = blake2b_simd::blake2b(data);
+        let param_data = |digest: &str| -> ParameterData {
+            serde_json::from_str(&format!(
+                r#"{{"cid": "QmUa7f9JtJMsqJJ3s3ZXk6WyF4xJLE8FiqYskZGgk8GCDv", "digest": "{digest}", "sector_size": 2048}}"#
+            ))
+            .unwrap()
+        };
+
+        // 32 characters, truncated BLAKE2b-256.
+        let truncated = hex::encode(blake2b.as_bytes().get(..PROOF_DIGEST_LEN).unwrap());
+        assert_eq!(truncated.len(), 32);
+        // 64 characters, full-length BLAKE2b-256 or SHA-256.
+        let full_blake2b = hex::encode(
+            blake2b_simd::Params::new()
+                .hash_length(FULL_DIGEST_LEN)
+                .hash(data)
+                .as_bytes(),
+        );
+        let sha256 = hex::encode(Sha256::digest(data));
+        assert_eq!((full_blake2b.len(), sha256.len()), (64, 64));
+        for digest in [&truncated, &full_blake2b, &sha256] {
+            check_parameter_file_inner(
+                &path,
+                &param_data(digest),
+                &default_digest(),
+                None,
+                false,
+                None,
+            )
+            .await
+            .unwrap();
+        }
+
+        for digest in ["00".repeat(PROOF_DIGEST_LEN), "00".repeat(FULL_DIGEST_LEN)] {
+            let err = check_parameter_file_inner(
+                &path,
+                &param_data(&digest),
+                &default_digest(),
+                None,
+                false,
+                None,
+            )
+            .await
+            .unwrap_err();
+            assert!(matches!(
+                err.downcast_ref::<ParamFetchError>(),
+                Some(ParamFetchError::ChecksumMismatch { .. })
+            ));
+        }
+        // Neither length.
+        serde_json::from_str::<ParamDigest>(&format!("\"{}\"", "00".repeat(20))).unwrap_err();
+
+        // A custom digest function is used whatever the length of the expected digest.
+        let sha256_digest: DigestFn = Arc::new(|path, _| {
+            let data = std::fs::read(path);
+            async move { Ok(hex::encode(Sha256::digest(data?))) }.boxed()
+        });
+        check_parameter_file_inner(
+            &path,
+            &param_data(&sha256),
+            &sha256_digest,
+            None,
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+        // It does not fall back to the built-in algorithms, and a digest of the wrong length is
+        // reported as such rather than as a checksum mismatch.
+        let err = check_parameter_file_inner(
+            &path,
+            &param_data(&truncated),
+            &sha256_digest,
+            None,
+            false,
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.downcast_ref::<ParamFetchError>().is_none());
+        assert!(err.to_string().contains("configured digest function"));
+    }
+
+    #[tokio::test]
+    async fn test_blake2b_digest_cancellation() {
+        let tempfile = tempfile::Builder::new().tempfile().unwrap();
+        let path = tempfile.path();
+        let chunks = 16;
+        std::fs::write(path, vec![0xaa; chunks * HASH_CHUNK_SIZE]).unwrap();
+
+        // Slow down the hashing so that it is cancelled halfway through.
+        let hashed = Arc::new(AtomicU64::new(0));
+        let on_hashed: HashProgressFn = {
+            let hashed = hashed.clone();
+            Arc::new(move |n| {
+                hashed.store(n, Ordering::Relaxed);
+                std::thread::sleep(std::time::Duration::from_millis(50));
+            })
+        };
+        tokio::time::timeout(
+            std::time::Duration::from_millis(100),
+            blake2b_digest(path, Some(on_hashed)),
+        )
+        .await
+        .unwrap_err();
+
+        // The blocking task stops after the chunk being hashed when cancelled.
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        let stopped_at = hashed.load(Ordering::Relaxed);
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        assert_eq!(hashed.load(Ordering::Relaxed), stopped_at);
+        assert!(stopped_at < (chunks * HASH_CHUNK_SIZE) as u64);
     }
 }
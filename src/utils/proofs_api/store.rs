@@ -0,0 +1,76 @@
+// Copyright 2019-2024 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! Storage of the proofs parameter files, see [`ParamStore`].
+
+use std::{
+    io::{self, ErrorKind},
+    path::PathBuf,
+    pin::Pin,
+};
+
+use async_trait::async_trait;
+use futures::io::{AsyncRead, AsyncWrite};
+
+/// Reader of a stored parameter file.
+pub type ParamReader = Pin<Box<dyn AsyncRead + Send>>;
+
+/// Writer of a stored parameter file. The file is complete once the writer is closed.
+pub type ParamWriter = Pin<Box<dyn AsyncWrite + Send>>;
+
+/// Storage the parameter files are read from and written to, by name, e.g., an object storage
+/// bucket for cloud deployments. See [`super::ParamFetchConfig::store`].
+#[async_trait]
+pub trait ParamStore: Send + Sync {
+    /// Returns whether the parameter file is stored.
+    async fn exists(&self, name: &str) -> io::Result<bool>;
+
+    /// Opens the stored parameter file for reading, failing with [`ErrorKind::NotFound`] if it
+    /// is not stored.
+    async fn open_reader(&self, name: &str) -> io::Result<ParamReader>;
+
+    /// Creates the parameter file, replacing any stored one.
+    async fn create_writer(&self, name: &str) -> io::Result<ParamWriter>;
+
+    /// Removes the stored parameter file, if any.
+    async fn remove(&self, name: &str) -> io::Result<()>;
+}
+
+/// [`ParamStore`] keeping the parameter files in a local directory.
+#[derive(Debug, Clone)]
+pub struct LocalParamStore {
+    dir: PathBuf,
+}
+
+impl LocalParamStore {
+    /// Creates a store of the parameter files in the given directory.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl ParamStore for LocalParamStore {
+    async fn exists(&self, name: &str) -> io::Result<bool> {
+        match async_fs::metadata(self.dir.join(name)).await {
+            Ok(_) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn open_reader(&self, name: &str) -> io::Result<ParamReader> {
+        Ok(Box::pin(async_fs::File::open(self.dir.join(name)).await?))
+    }
+
+    async fn create_writer(&self, name: &str) -> io::Result<ParamWriter> {
+        async_fs::create_dir_all(&self.dir).await?;
+        Ok(Box::pin(async_fs::File::create(self.dir.join(name)).await?))
+    }
+
+    async fn remove(&self, name: &str) -> io::Result<()> {
+        match async_fs::remove_file(self.dir.join(name)).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
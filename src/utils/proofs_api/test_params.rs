@@ -0,0 +1,56 @@
+// Copyright 2019-2024 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Parameter files and manifests shared by the parameter tests.
+
+use super::parameters::param_dir;
+use std::path::PathBuf;
+
+/// Content of the valid parameter files.
+pub const CONTENT: &[u8] = b"Ph'nglui mglw'nafh Cthulhu R'lyeh wgah'nagl fhtagn";
+/// Content of the corrupt parameter files, not matching the digest of [`CONTENT`].
+pub const CORRUPT: &[u8] = b"Cthulhu fhtagn!";
+/// CID of the manifest entries.
+pub const CID: &str = "QmUa7f9JtJMsqJJ3s3ZXk6WyF4xJLE8FiqYskZGgk8GCDv";
+
+/// Returns the truncated BLAKE2b-256 digest of the content, as found in the manifest.
+pub fn digest(content: &[u8]) -> String {
+    hex::encode(blake2b_simd::blake2b(content).as_bytes().get(..16).unwrap())
+}
+
+/// Returns the manifest entry of a file with the given content, for the given sector size.
+/// Further fields, e.g., `size` or `optional`, can be set on the returned value.
+pub fn entry(content: &[u8], sector_size: u64) -> serde_json::Value {
+    serde_json::json!({
+        "cid": CID,
+        "digest": digest(content),
+        "sector_size": sector_size,
+    })
+}
+
+/// Returns the manifest made of the given named entries.
+pub fn manifest<N: Into<String>>(
+    entries: impl IntoIterator<Item = (N, serde_json::Value)>,
+) -> String {
+    serde_json::Value::Object(
+        entries
+            .into_iter()
+            .map(|(name, entry)| (name.into(), entry))
+            .collect(),
+    )
+    .to_string()
+}
+
+/// Returns the manifest of the given files of [`CONTENT`], for 2KiB sectors.
+pub fn manifest_of<N: Into<String>>(names: impl IntoIterator<Item = N>) -> String {
+    manifest(names.into_iter().map(|name| (name, entry(CONTENT, 2048))))
+}
+
+/// Creates a temporary data directory along with its parameter directory, whose path is
+/// returned.
+pub fn data_dir() -> (tempfile::TempDir, PathBuf) {
+    let data_dir = tempfile::tempdir().unwrap();
+    let dir = param_dir(data_dir.path());
+    std::fs::create_dir_all(&dir).unwrap();
+    (data_dir, dir)
+}
@@ -0,0 +1,839 @@
+// Copyright 2019-2024 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! Read-only verification of the proofs parameters already present in the cache directory.
+//! Unlike [`super::get_params`], nothing is downloaded; the result is a machine-readable report
+//! which can be used, e.g., for gating CI jobs.
+
+use std::{
+    collections::BTreeSet,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use futures::{AsyncRead, Stream, StreamExt as _};
+use once_cell::sync::Lazy;
+use prometheus_client::metrics::gauge::Gauge;
+use serde::Serialize;
+use tokio_util::compat::TokioAsyncReadCompatExt as _;
+use tracing::{info, warn};
+
+use crate::shim::sector::SectorSize;
+
+use super::errors::ParamFetchError;
+use super::parameters::{
+    check_parameter_file, check_parameter_file_uncached, check_reader, manifest_entry, param_dir,
+    parse_manifest, HashProgressFn, ParameterData, ParameterMap, DEFAULT_DIGEST,
+};
+use super::paramfetch::{
+    get_params, hash_progress, manifest_entries, ParamFetchConfig, SectorSizeOpt,
+};
+
+/// Number of parameter files found corrupt by the last pass of [`spawn_param_revalidator`].
+static PARAM_FILES_CORRUPT: Lazy<Gauge> = Lazy::new(|| {
+    let metric = Gauge::default();
+    crate::metrics::default_registry().register(
+        "proof_params_corrupt",
+        "Number of proof parameter files found corrupt by the last background verification",
+        metric.clone(),
+    );
+    metric
+});
+
+/// Maximum number of parameter files hashed concurrently during verification.
+const VERIFY_CONCURRENCY: usize = 4;
+
+/// State of a single parameter file in the cache directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamFileStatus {
+    /// The file is present and matches the manifest digest.
+    Ok,
+    /// The file is not present.
+    Missing,
+    /// The file is present and has the size declared in the manifest, if any, but does not match
+    /// the manifest digest.
+    Corrupt,
+    /// The file is present but does not have the size declared in the manifest, e.g., it was
+    /// truncated. Detected without hashing the file.
+    WrongSize,
+    /// The file is present but cannot be read. Downloading it again would not help, so it is
+    /// reported rather than repaired.
+    PermissionDenied,
+    /// The file is optional and not present, see [`ParamFetchConfig::include_optional`].
+    Skipped,
+}
+
+/// Verification result of a single parameter file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParamVerifyEntry {
+    /// Name of the parameter file.
+    pub name: String,
+    /// State of the parameter file.
+    pub status: ParamFileStatus,
+}
+
+/// Summary of a verification pass over the parameter files.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct ParamVerifyReport {
+    /// Number of parameter files checked.
+    pub total: usize,
+    /// Number of valid parameter files.
+    pub ok: usize,
+    /// Number of missing parameter files.
+    pub missing: usize,
+    /// Number of parameter files not matching the manifest digest.
+    pub corrupt: usize,
+    /// Number of parameter files not matching the manifest size.
+    pub wrong_size: usize,
+    /// Number of parameter files which cannot be read.
+    pub permission_denied: usize,
+    /// Number of missing optional parameter files.
+    pub skipped: usize,
+    /// Per-file results, sorted by name.
+    pub entries: Vec<ParamVerifyEntry>,
+}
+
+impl ParamVerifyReport {
+    /// Returns `true` if all the required parameter files are present and all the present ones
+    /// are valid.
+    pub fn is_ok(&self) -> bool {
+        self.ok + self.skipped == self.total
+    }
+}
+
+impl FromIterator<ParamVerifyEntry> for ParamVerifyReport {
+    fn from_iter<T: IntoIterator<Item = ParamVerifyEntry>>(iter: T) -> Self {
+        let mut report = Self::default();
+        for entry in iter {
+            report.total += 1;
+            match entry.status {
+                ParamFileStatus::Ok => report.ok += 1,
+                ParamFileStatus::Missing => report.missing += 1,
+                ParamFileStatus::Corrupt => report.corrupt += 1,
+                ParamFileStatus::WrongSize => report.wrong_size += 1,
+                ParamFileStatus::PermissionDenied => report.permission_denied += 1,
+                ParamFileStatus::Skipped => report.skipped += 1,
+            }
+            report.entries.push(entry);
+        }
+        report.entries.sort_by(|a, b| a.name.cmp(&b.name));
+        report
+    }
+}
+
+/// Verifies the parameter files selected by `storage_size` against the given manifest, without
+/// downloading anything.
+pub async fn verify_params(
+    data_dir: &Path,
+    param_json: &str,
+    storage_size: SectorSizeOpt,
+    config: &ParamFetchConfig,
+) -> anyhow::Result<ParamVerifyReport> {
+    let entries = verify_params_stream(data_dir, param_json, storage_size, config)?
+        .collect::<Vec<_>>()
+        .await;
+    Ok(entries.into_iter().collect())
+}
+
+/// Verifies the parameter files like [`verify_params`], yielding the result of each file as soon
+/// as it is checked rather than once all of them are, e.g., for updating a live dashboard. At
+/// most [`VERIFY_CONCURRENCY`] files are hashed at once.
+pub fn verify_params_stream<'a>(
+    data_dir: &Path,
+    param_json: &str,
+    storage_size: SectorSizeOpt,
+    config: &'a ParamFetchConfig,
+) -> anyhow::Result<impl Stream<Item = ParamVerifyEntry> + 'a> {
+    let dir = param_dir(data_dir);
+    let entries = manifest_entries(param_json, storage_size)?;
+    Ok(futures::stream::iter(entries)
+        .map(move |(name, info)| {
+            let path = dir.join(&name);
+            let on_hashed = hash_progress(&name, &path, config);
+            verify_param_file(name, path, info, config, on_hashed, true)
+        })
+        .buffer_unordered(VERIFY_CONCURRENCY))
+}
+
+/// Verifies the parameter files like [`verify_params`], hashing at most `concurrency` files at
+/// once, e.g., for the whole cache at startup. Rather than per file, `progress` is invoked with
+/// the total number of bytes hashed so far across all the files, which only ever increases.
+pub async fn verify_all(
+    data_dir: &Path,
+    param_json: &str,
+    storage_size: SectorSizeOpt,
+    config: &ParamFetchConfig,
+    concurrency: usize,
+    progress: HashProgressFn,
+) -> anyhow::Result<ParamVerifyReport> {
+    let dir = param_dir(data_dir);
+    let total = Arc::new(parking_lot::Mutex::new(0));
+    let entries = futures::stream::iter(manifest_entries(param_json, storage_size)?)
+        .map(|(name, info)| {
+            let path = dir.join(&name);
+            let on_hashed = aggregate_progress(total.clone(), progress.clone());
+            verify_param_file(name, path, info, config, Some(on_hashed), true)
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await;
+    Ok(entries.into_iter().collect())
+}
+
+/// Returns the progress callback of a single file, adding the bytes newly hashed from it to the
+/// `total` shared by all the files before reporting the latter to `progress`. The lock is held
+/// while reporting so that the totals are reported in order.
+fn aggregate_progress(
+    total: Arc<parking_lot::Mutex<u64>>,
+    progress: HashProgressFn,
+) -> HashProgressFn {
+    let file_hashed = AtomicU64::new(0);
+    Arc::new(move |hashed| {
+        let new = hashed.saturating_sub(file_hashed.swap(hashed, Ordering::Relaxed));
+        if new > 0 {
+            let mut total = total.lock();
+            *total += new;
+            progress(*total);
+        }
+    })
+}
+
+/// Returns the sector sizes whose proofs can be verified with the parameter files present in the
+/// cache directory, i.e., whose verification files, see [`SectorSizeOpt::KeysForSize`], are all
+/// present and valid. Optional files are not required.
+pub async fn supported_proofs(
+    data_dir: &Path,
+    param_json: &str,
+) -> anyhow::Result<BTreeSet<SectorSize>> {
+    let report = verify_params(
+        data_dir,
+        param_json,
+        SectorSizeOpt::Keys,
+        &ParamFetchConfig::default(),
+    )
+    .await?;
+    let valid = |name: &str| {
+        report.entries.iter().any(|entry| {
+            entry.name == name
+                && matches!(entry.status, ParamFileStatus::Ok | ParamFileStatus::Skipped)
+        })
+    };
+    let mut supported = BTreeSet::new();
+    for size in manifest_entries(param_json, SectorSizeOpt::All)?
+        .into_iter()
+        .filter_map(|(_, info)| SectorSize::from_bytes(info.sector_size))
+        .collect::<BTreeSet<_>>()
+    {
+        if manifest_entries(param_json, SectorSizeOpt::KeysForSize(size))?
+            .iter()
+            .all(|(name, _)| valid(name))
+        {
+            supported.insert(size);
+        }
+    }
+    Ok(supported)
+}
+
+/// Verifies the parameter file `name` piped on stdin against its entry in the given manifest, e.g.,
+/// for checking a file streamed from a remote host without storing it first.
+pub async fn verify_stdin(name: &str, param_json: &str) -> anyhow::Result<()> {
+    verify_reader(tokio::io::stdin().compat(), name, param_json).await
+}
+
+async fn verify_reader(
+    reader: impl AsyncRead + Unpin,
+    name: &str,
+    param_json: &str,
+) -> anyhow::Result<()> {
+    let info = manifest_entry(param_json, name)?;
+    check_reader(reader, name, &info).await
+}
+
+/// Opens the parameter file `name` in the cache directory for reading, once verified against its
+/// manifest entry `info`, so that callers can stream its bytes without deriving its path nor
+/// verifying it themselves. Cached verifications are trusted, like when fetching the parameters.
+pub async fn open_verified(
+    data_dir: &Path,
+    name: &str,
+    info: &ParameterData,
+) -> anyhow::Result<impl AsyncRead + Unpin> {
+    let path = param_dir(data_dir).join(name);
+    check_parameter_file(&path, info, &DEFAULT_DIGEST, None, None).await?;
+    Ok(tokio::fs::File::open(&path).await?.compat())
+}
+
+/// Spawns a task verifying the parameter files selected by `storage_size` every `interval`, like
+/// [`verify_params`], to catch the ones gone bad on long-running nodes, e.g., due to bit rot or
+/// tampering. The files are hashed with `config`, ignoring the cached digests, see
+/// [`revalidate_params`]. Corrupt files are logged and counted in the `proof_params_corrupt`
+/// metric, and fetched again if `repair` is set. Missing files are not reported.
+pub fn spawn_param_revalidator(
+    data_dir: PathBuf,
+    param_json: String,
+    storage_size: SectorSizeOpt,
+    interval: Duration,
+    repair: bool,
+    config: ParamFetchConfig,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            let corrupt =
+                match revalidate_params(&data_dir, &param_json, storage_size, &config).await {
+                    Ok(corrupt) => corrupt,
+                    Err(e) => {
+                        warn!("Failed to verify the parameter files: {e:#}");
+                        continue;
+                    }
+                };
+            PARAM_FILES_CORRUPT.set(corrupt.len() as i64);
+            if corrupt.is_empty() {
+                continue;
+            }
+            warn!("Corrupt parameter files: {}", corrupt.join(", "));
+            if repair {
+                match repair_params(&data_dir, &param_json, &corrupt, &config).await {
+                    Ok(()) => info!("Repaired the corrupt parameter files"),
+                    Err(e) => warn!("Failed to repair the corrupt parameter files: {e:#}"),
+                }
+            }
+        }
+    })
+}
+
+/// Hashes the present parameter files selected by `storage_size` again, ignoring the cached
+/// digests which would hide the files corrupted in place, and returns the names of the corrupt
+/// ones, which are evicted from the caches. Unreadable files are logged but not returned, as
+/// fetching them again would not help.
+async fn revalidate_params(
+    data_dir: &Path,
+    param_json: &str,
+    storage_size: SectorSizeOpt,
+    config: &ParamFetchConfig,
+) -> anyhow::Result<Vec<String>> {
+    let dir = param_dir(data_dir);
+    let entries = futures::stream::iter(manifest_entries(param_json, storage_size)?)
+        .map(|(name, info)| {
+            let path = dir.join(&name);
+            verify_param_file(name, path, info, config, None, false)
+        })
+        .buffer_unordered(VERIFY_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+    let report = ParamVerifyReport::from_iter(entries);
+    let unreadable: Vec<_> = report
+        .entries
+        .iter()
+        .filter(|entry| entry.status == ParamFileStatus::PermissionDenied)
+        .map(|entry| entry.name.as_str())
+        .collect();
+    if !unreadable.is_empty() {
+        warn!("Unreadable parameter files: {}", unreadable.join(", "));
+    }
+    Ok(report
+        .entries
+        .into_iter()
+        .filter(|entry| {
+            matches!(
+                entry.status,
+                ParamFileStatus::Corrupt | ParamFileStatus::WrongSize
+            )
+        })
+        .map(|entry| entry.name)
+        .collect())
+}
+
+/// Fetches the given corrupt parameter files again.
+async fn repair_params(
+    data_dir: &Path,
+    param_json: &str,
+    corrupt: &[String],
+    config: &ParamFetchConfig,
+) -> anyhow::Result<()> {
+    let manifest: ParameterMap = parse_manifest(param_json)?
+        .into_iter()
+        .filter(|(name, _)| corrupt.contains(name))
+        .collect();
+    get_params(
+        data_dir,
+        &serde_json::to_string(&manifest)?,
+        SectorSizeOpt::All,
+        false,
+        config,
+    )
+    .await?;
+    Ok(())
+}
+
+async fn verify_param_file(
+    name: String,
+    path: PathBuf,
+    info: ParameterData,
+    config: &ParamFetchConfig,
+    on_hashed: Option<HashProgressFn>,
+    use_cache: bool,
+) -> ParamVerifyEntry {
+    let pool = config.hash_pool.as_ref();
+    let result = if use_cache {
+        check_parameter_file(&path, &info, &config.digest, on_hashed, pool).await
+    } else {
+        check_parameter_file_uncached(&path, &info, &config.digest, on_hashed, pool).await
+    };
+    let status = match result {
+        Ok(()) => ParamFileStatus::Ok,
+        Err(e)
+            if e.downcast_ref::<io::Error>()
+                .is_some_and(|e| e.kind() == ErrorKind::NotFound) =>
+        {
+            if info.optional && !config.include_optional {
+                ParamFileStatus::Skipped
+            } else {
+                ParamFileStatus::Missing
+            }
+        }
+        Err(e) => match e.downcast_ref() {
+            Some(ParamFetchError::SizeMismatch { .. }) => ParamFileStatus::WrongSize,
+            Some(ParamFetchError::PermissionDenied { .. }) => ParamFileStatus::PermissionDenied,
+            _ => ParamFileStatus::Corrupt,
+        },
+    };
+    ParamVerifyEntry { name, status }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::proofs_api::parameters::blake2b_digest;
+    use crate::utils::proofs_api::test_params::{self, CONTENT, CORRUPT};
+    use futures::AsyncReadExt as _;
+
+    #[test]
+    fn test_verify_report_json() {
+        let report: ParamVerifyReport = [
+            ("v28-b.vk", ParamFileStatus::Missing),
+            ("v28-a.vk", ParamFileStatus::Ok),
+            ("v28-c.params", ParamFileStatus::Corrupt),
+            ("v28-d.params", ParamFileStatus::Ok),
+            ("v28-e.params", ParamFileStatus::WrongSize),
+            ("v28-f.vk", ParamFileStatus::PermissionDenied),
+        ]
+        .into_iter()
+        .map(|(name, status)| ParamVerifyEntry {
+            name: name.into(),
+            status,
+        })
+        .collect();
+
+        assert!(!report.is_ok());
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            serde_json::json!({
+                "total": 6,
+                "ok": 2,
+                "missing": 1,
+                "corrupt": 1,
+                "wrong_size": 1,
+                "permission_denied": 1,
+                "skipped": 0,
+                "entries": [
+                    { "name": "v28-a.vk", "status": "ok" },
+                    { "name": "v28-b.vk", "status": "missing" },
+                    { "name": "v28-c.params", "status": "corrupt" },
+                    { "name": "v28-d.params", "status": "ok" },
+                    { "name": "v28-e.params", "status": "wrong_size" },
+                    { "name": "v28-f.vk", "status": "permission_denied" },
+                ]
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_params_stream() {
+        let (data_dir, dir) = test_params::data_dir();
+        let names: Vec<_> = (0..10).map(|i| format!("v28-{i}.vk")).collect();
+        let param_json = test_params::manifest_of(&names);
+        // Every other file is present, one of them corrupt.
+        for name in names.iter().step_by(2) {
+            std::fs::write(dir.join(name), CONTENT).unwrap();
+        }
+        std::fs::write(dir.join("v28-0.vk"), CORRUPT).unwrap();
+
+        let config = ParamFetchConfig::default();
+        let mut entries: Vec<_> =
+            verify_params_stream(data_dir.path(), &param_json, SectorSizeOpt::All, &config)
+                .unwrap()
+                .collect()
+                .await;
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(entries.len(), names.len());
+        for entry in entries {
+            let i: usize = entry
+                .name
+                .trim_start_matches("v28-")
+                .trim_end_matches(".vk")
+                .parse()
+                .unwrap();
+            let expected = match i {
+                0 => ParamFileStatus::Corrupt,
+                i if i % 2 == 0 => ParamFileStatus::Ok,
+                _ => ParamFileStatus::Missing,
+            };
+            assert_eq!(entry.status, expected, "{}", entry.name);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verify_all_aggregate_progress() {
+        let (data_dir, dir) = test_params::data_dir();
+        let contents: Vec<_> = (1..=6).map(|i| CONTENT.repeat(i * 1000)).collect();
+        let param_json = test_params::manifest(
+            contents
+                .iter()
+                .enumerate()
+                .map(|(i, content)| (format!("v28-{i}.vk"), test_params::entry(content, 2048))),
+        );
+        for (i, content) in contents.iter().enumerate() {
+            std::fs::write(dir.join(format!("v28-{i}.vk")), content).unwrap();
+        }
+        let total: u64 = contents.iter().map(|content| content.len() as u64).sum();
+
+        let reported = Arc::new(parking_lot::Mutex::new(vec![]));
+        let progress: HashProgressFn = {
+            let reported = reported.clone();
+            Arc::new(move |hashed| reported.lock().push(hashed))
+        };
+        // The given configuration is used, e.g., its digest function.
+        let hashed_files = Arc::new(AtomicU64::new(0));
+        let config = ParamFetchConfig {
+            digest: {
+                let hashed_files = hashed_files.clone();
+                Arc::new(move |path: &Path, on_hashed: Option<HashProgressFn>| {
+                    hashed_files.fetch_add(1, Ordering::Relaxed);
+                    blake2b_digest(path, on_hashed)
+                })
+            },
+            ..Default::default()
+        };
+        let report = verify_all(
+            data_dir.path(),
+            &param_json,
+            SectorSizeOpt::All,
+            &config,
+            3,
+            progress,
+        )
+        .await
+        .unwrap();
+        assert!(report.is_ok());
+        assert_eq!(report.ok, contents.len());
+        assert_eq!(hashed_files.load(Ordering::Relaxed), contents.len() as u64);
+        let reported = reported.lock();
+        assert!(reported.windows(2).all(|w| w[0] < w[1]), "{reported:?}");
+        assert_eq!(reported.last(), Some(&total));
+        assert_eq!(
+            reported.iter().filter(|hashed| **hashed == total).count(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_wrong_size_or_hash() {
+        let (data_dir, dir) = test_params::data_dir();
+        let mut entry = test_params::entry(CONTENT, 2048);
+        entry["size"] = CONTENT.len().into();
+        let param_json = test_params::manifest(
+            ["v28-ok.vk", "v28-truncated.vk", "v28-corrupt.vk"].map(|name| (name, entry.clone())),
+        );
+        std::fs::write(dir.join("v28-ok.vk"), CONTENT).unwrap();
+        std::fs::write(dir.join("v28-truncated.vk"), CONTENT.get(..20).unwrap()).unwrap();
+        let mut corrupt = CONTENT.to_vec();
+        *corrupt.get_mut(20).unwrap() ^= 1;
+        std::fs::write(dir.join("v28-corrupt.vk"), corrupt).unwrap();
+
+        let hashed = Arc::new(parking_lot::Mutex::new(vec![]));
+        let config = ParamFetchConfig {
+            digest: {
+                let hashed = hashed.clone();
+                Arc::new(move |path: &Path, on_hashed: Option<HashProgressFn>| {
+                    hashed.lock().push(path.file_name().unwrap().to_owned());
+                    blake2b_digest(path, on_hashed)
+                })
+            },
+            ..Default::default()
+        };
+        let report = verify_params(data_dir.path(), &param_json, SectorSizeOpt::All, &config)
+            .await
+            .unwrap();
+        let status = |name: &str| {
+            report
+                .entries
+                .iter()
+                .find(|entry| entry.name == name)
+                .map(|entry| entry.status)
+        };
+        assert_eq!(status("v28-ok.vk"), Some(ParamFileStatus::Ok));
+        assert_eq!(status("v28-truncated.vk"), Some(ParamFileStatus::WrongSize));
+        assert_eq!(status("v28-corrupt.vk"), Some(ParamFileStatus::Corrupt));
+        assert_eq!((report.corrupt, report.wrong_size), (1, 1));
+        // The truncated file is told apart without hashing it.
+        let mut hashed = hashed.lock().clone();
+        hashed.sort();
+        assert_eq!(hashed, ["v28-corrupt.vk", "v28-ok.vk"]);
+    }
+
+    #[tokio::test]
+    async fn test_supported_proofs() {
+        let (data_dir, dir) = test_params::data_dir();
+        let param_json = test_params::manifest(
+            [
+                ("v28-a.vk", 2048),
+                ("v28-a.params", 2048),
+                ("v28-b.vk", 8388608),
+                ("v28-b.params", 8388608),
+                ("v28-c.vk", 34359738368),
+                ("v28-inner-product.srs", 0),
+            ]
+            .map(|(name, size)| (name, test_params::entry(CONTENT, size))),
+        );
+        // The proving parameters are not needed, the verification key of the 8MiB sectors is
+        // corrupt and the one of the 32GiB sectors is missing.
+        for name in ["v28-a.vk", "v28-inner-product.srs"] {
+            std::fs::write(dir.join(name), CONTENT).unwrap();
+        }
+        std::fs::write(dir.join("v28-b.vk"), CORRUPT).unwrap();
+
+        assert_eq!(
+            supported_proofs(data_dir.path(), &param_json)
+                .await
+                .unwrap(),
+            BTreeSet::from([SectorSize::_2KiB])
+        );
+
+        // All the sector sizes need the files not bound to one.
+        std::fs::remove_file(dir.join("v28-inner-product.srs")).unwrap();
+        assert!(supported_proofs(data_dir.path(), &param_json)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_open_verified() {
+        let (data_dir, dir) = test_params::data_dir();
+        let param_json = test_params::manifest_of(["v28-test.vk"]);
+        let info = manifest_entry(&param_json, "v28-test.vk").unwrap();
+
+        // Missing files cannot be opened.
+        assert!(open_verified(data_dir.path(), "v28-test.vk", &info)
+            .await
+            .is_err());
+
+        std::fs::write(dir.join("v28-test.vk"), CONTENT).unwrap();
+        let mut reader = open_verified(data_dir.path(), "v28-test.vk", &info)
+            .await
+            .unwrap();
+        let mut read = vec![];
+        reader.read_to_end(&mut read).await.unwrap();
+        assert_eq!(read, CONTENT);
+
+        // Neither can corrupt ones.
+        std::fs::write(dir.join("v28-test.vk"), CORRUPT).unwrap();
+        let e = open_verified(data_dir.path(), "v28-test.vk", &info)
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(
+            e.downcast_ref(),
+            Some(ParamFetchError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_param_revalidator() {
+        let (data_dir, dir) = test_params::data_dir();
+        let param_json = test_params::manifest_of(["v28-test.vk", "v28-missing.vk"]);
+        std::fs::write(dir.join("v28-test.vk"), CONTENT).unwrap();
+
+        let revalidator = spawn_param_revalidator(
+            data_dir.path().to_owned(),
+            param_json,
+            SectorSizeOpt::All,
+            Duration::from_millis(10),
+            false,
+            ParamFetchConfig::default(),
+        );
+        let corrupt = |expected| async move {
+            tokio::time::timeout(Duration::from_secs(10), async {
+                while PARAM_FILES_CORRUPT.get() != expected {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            })
+            .await
+        };
+        // Missing files are not reported.
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(PARAM_FILES_CORRUPT.get(), 0);
+
+        std::fs::write(dir.join("v28-test.vk"), CORRUPT).unwrap();
+        corrupt(1).await.unwrap();
+        revalidator.abort();
+    }
+
+    #[tokio::test]
+    async fn test_revalidate_params_uncached() {
+        let (data_dir, dir) = test_params::data_dir();
+        let param_json = test_params::manifest_of(["v28-test.vk"]);
+        let path = dir.join("v28-test.vk");
+        std::fs::write(&path, CONTENT).unwrap();
+        let config = ParamFetchConfig::default();
+        let status = || async {
+            verify_params(data_dir.path(), &param_json, SectorSizeOpt::All, &config)
+                .await
+                .unwrap()
+                .entries
+                .first()
+                .map(|entry| entry.status)
+        };
+        // Caches the digest, if enabled.
+        assert_eq!(status().await, Some(ParamFileStatus::Ok));
+
+        // Corrupt the file in place, keeping its size and modification time.
+        let mtime = std::fs::metadata(&path).unwrap().modified().unwrap();
+        let mut corrupt = CONTENT.to_vec();
+        *corrupt.get_mut(20).unwrap() ^= 1;
+        std::fs::write(&path, corrupt).unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+        #[cfg(any(all(unix, feature = "xattr"), feature = "digest-index"))]
+        assert_eq!(status().await, Some(ParamFileStatus::Ok));
+
+        assert_eq!(
+            revalidate_params(data_dir.path(), &param_json, SectorSizeOpt::All, &config)
+                .await
+                .unwrap(),
+            ["v28-test.vk"]
+        );
+        // The corrupt file is no longer trusted.
+        assert_eq!(status().await, Some(ParamFileStatus::Corrupt));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_verify_permission_denied() {
+        use std::os::unix::fs::{MetadataExt as _, PermissionsExt as _};
+
+        let (data_dir, dir) = test_params::data_dir();
+        let param_json = test_params::manifest_of(["v28-unreadable.vk"]);
+        let path = dir.join("v28-unreadable.vk");
+        std::fs::write(&path, CONTENT).unwrap();
+        // The file is owned by the effective user. Root bypasses the permissions.
+        if std::fs::metadata(&path).unwrap().uid() == 0 {
+            eprintln!("skipping test_verify_permission_denied: running as root");
+            return;
+        }
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o000)).unwrap();
+        std::fs::File::open(&path).unwrap_err();
+        let config = ParamFetchConfig::default();
+
+        let report = verify_params(data_dir.path(), &param_json, SectorSizeOpt::All, &config)
+            .await
+            .unwrap();
+        assert_eq!((report.permission_denied, report.corrupt), (1, 0));
+        // The file is not to be repaired.
+        assert!(
+            revalidate_params(data_dir.path(), &param_json, SectorSizeOpt::All, &config)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_optional() {
+        let (data_dir, dir) = test_params::data_dir();
+        let mut optional = test_params::entry(CONTENT, 2048);
+        optional["optional"] = true.into();
+        let param_json = test_params::manifest([
+            ("v28-required.vk", test_params::entry(CONTENT, 2048)),
+            ("v28-optional-present.vk", optional.clone()),
+            ("v28-optional-missing.vk", optional),
+        ]);
+        std::fs::write(dir.join("v28-optional-present.vk"), CONTENT).unwrap();
+        let status = |report: &ParamVerifyReport, name: &str| {
+            report
+                .entries
+                .iter()
+                .find(|entry| entry.name == name)
+                .map(|entry| entry.status)
+        };
+        let config = ParamFetchConfig::default();
+
+        // The required file is missing.
+        let report = verify_params(data_dir.path(), &param_json, SectorSizeOpt::All, &config)
+            .await
+            .unwrap();
+        assert!(!report.is_ok());
+        assert_eq!(
+            status(&report, "v28-required.vk"),
+            Some(ParamFileStatus::Missing)
+        );
+
+        std::fs::write(dir.join("v28-required.vk"), CONTENT).unwrap();
+        let report = verify_params(data_dir.path(), &param_json, SectorSizeOpt::All, &config)
+            .await
+            .unwrap();
+        assert!(report.is_ok());
+        assert_eq!(
+            status(&report, "v28-optional-present.vk"),
+            Some(ParamFileStatus::Ok)
+        );
+        assert_eq!(
+            status(&report, "v28-optional-missing.vk"),
+            Some(ParamFileStatus::Skipped)
+        );
+
+        // Unless the optional files are requested.
+        let config = ParamFetchConfig {
+            include_optional: true,
+            ..config
+        };
+        let report = verify_params(data_dir.path(), &param_json, SectorSizeOpt::All, &config)
+            .await
+            .unwrap();
+        assert!(!report.is_ok());
+        assert_eq!(
+            status(&report, "v28-optional-missing.vk"),
+            Some(ParamFileStatus::Missing)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_reader() {
+        let param_json = test_params::manifest_of(["v28-a.vk"]);
+
+        verify_reader(futures::io::Cursor::new(CONTENT), "v28-a.vk", &param_json)
+            .await
+            .unwrap();
+
+        let e = verify_reader(futures::io::Cursor::new(CORRUPT), "v28-a.vk", &param_json)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            e.downcast_ref(),
+            Some(ParamFetchError::ChecksumMismatch { .. })
+        ));
+
+        verify_reader(futures::io::Cursor::new(CONTENT), "v28-b.vk", &param_json)
+            .await
+            .unwrap_err();
+    }
+}